@@ -0,0 +1,210 @@
+/*! Byte readers for information memory stored in the separate address spaces
+of AVR.
+
+Information memory on AVR lives in either EEPROM or program memory (flash),
+neither of which can be read through a pointer. The readers in this module
+implement the `Iterator<Item = Result<u8, SequentialReadError>>` contract
+expected by [`from_seq_magic`](postcard_infomem::from_seq_magic) and friends
+(including [`from_seq_hardware`](postcard_infomem::from_seq_hardware)), so they
+can be passed directly to them:
+
+```ignore
+# use postcard_infomem_device::{avr::EepromReader, include_postcard_infomem};
+# use postcard_infomem::{from_seq_magic, InfoMem};
+include_postcard_infomem!("/path/to/binary/infomem/file");
+
+let mut buf = [0u8; 128];
+let im: InfoMem = from_seq_magic(EepromReader::new(infomem::get()), &mut buf).unwrap();
+```
+
+The EEPROM registers are assumed to be at the addresses used by megaAVR parts
+(e.g. ATmega328P, ATmega2560).
+
+In practice, we should make sure either:
+1. One thread (probably main) has access to EEPROM.
+2. If multiple threads (probably main and interrupts) need access, they
+   are using synchronization.
+
+It's likely not UB/not a data race, but multiple threads interleaving
+reads/writes is still probably not what you want.
+*/
+
+use core::arch::asm;
+use core::ops::Range;
+use core::ptr::{read_volatile, write_volatile};
+
+use postcard_infomem::SequentialReadError;
+
+// Data-space addresses of the EEPROM registers on megaAVR parts.
+const EECR: *mut u8 = 0x3f as *mut u8;
+const EEDR: *mut u8 = 0x40 as *mut u8;
+const EEARL: *mut u8 = 0x41 as *mut u8;
+const EEARH: *mut u8 = 0x42 as *mut u8;
+
+const EERE: u8 = 1 << 0;
+const EEPE: u8 = 1 << 1;
+
+#[cfg(feature = "avr-elpm")]
+// Data-space address of the RAMPZ register on megaAVR parts with ELPM.
+const RAMPZ: *mut u8 = 0x5b as *mut u8;
+
+/// Read a single byte of EEPROM at `addr`.
+pub fn read_eeprom(addr: usize) -> Result<u8, SequentialReadError> {
+    // SAFETY: The EEPROM registers exist at these addresses on all supported
+    // parts. Reading EEPROM has no side effects besides these registers.
+    unsafe {
+        // Wait for any previous write to finish.
+        while read_volatile(EECR) & EEPE != 0 {}
+
+        write_volatile(EEARH, (addr >> 8) as u8);
+        write_volatile(EEARL, addr as u8);
+        write_volatile(EECR, read_volatile(EECR) | EERE);
+
+        Ok(read_volatile(EEDR))
+    }
+}
+
+/** Read a single byte of program memory (flash) at `addr`, using the `lpm`
+instruction.
+
+Only the first 64 kiB of flash can be read this way; see `read_progmem_far`
+(with the `avr-elpm` feature). */
+pub fn read_progmem(addr: usize) -> Result<u8, SequentialReadError> {
+    let byte: u8;
+
+    // SAFETY: lpm only reads from flash, and any 16-bit address is valid.
+    unsafe {
+        asm!(
+            "lpm {}, Z",
+            out(reg) byte,
+            in("Z") addr as u16,
+            options(pure, readonly, nostack, preserves_flags)
+        );
+    }
+
+    Ok(byte)
+}
+
+#[cfg(feature = "avr-elpm")]
+/** Read a single byte of program memory (flash) at `addr`, using the `elpm`
+instruction.
+
+Unlike [`read_progmem`], this can read past the first 64 kiB of flash. Only
+available with the `avr-elpm` feature, for parts that implement `elpm` (e.g.
+ATmega2560). */
+pub fn read_progmem_far(addr: u32) -> Result<u8, SequentialReadError> {
+    let byte: u8;
+
+    // SAFETY: elpm only reads from flash. RAMPZ is restored afterwards, since
+    // other code may rely on it.
+    unsafe {
+        let rampz = read_volatile(RAMPZ);
+        write_volatile(RAMPZ, (addr >> 16) as u8);
+        asm!(
+            "elpm {}, Z",
+            out(reg) byte,
+            in("Z") addr as u16,
+            options(nostack, preserves_flags)
+        );
+        write_volatile(RAMPZ, rampz);
+    }
+
+    Ok(byte)
+}
+
+/** Reader for information memory stored in EEPROM, yielding one byte for each
+address produced by `A`.
+
+This is the default location for information memory on AVR. */
+pub struct EepromReader<A = Range<usize>>(A);
+
+impl<A> EepromReader<A>
+where
+    A: Iterator<Item = usize>,
+{
+    /** Create a reader over EEPROM addresses, probably the return value of
+    `generated_module_name::get()`. */
+    pub fn new<I>(addrs: I) -> Self
+    where
+        I: IntoIterator<IntoIter = A>,
+    {
+        Self(addrs.into_iter())
+    }
+}
+
+impl<A> Iterator for EepromReader<A>
+where
+    A: Iterator<Item = usize>,
+{
+    type Item = Result<u8, SequentialReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(read_eeprom)
+    }
+}
+
+/** Reader for information memory stored in program memory (flash), yielding
+one byte for each address produced by `A`.
+
+Information memory is placed in flash by the `memory = progmem` option of
+[`include_postcard_infomem`](crate::include_postcard_infomem). */
+pub struct ProgmemReader<A = Range<usize>>(A);
+
+impl<A> ProgmemReader<A>
+where
+    A: Iterator<Item = usize>,
+{
+    /** Create a reader over flash addresses, probably the return value of
+    `generated_module_name::get()`. */
+    pub fn new<I>(addrs: I) -> Self
+    where
+        I: IntoIterator<IntoIter = A>,
+    {
+        Self(addrs.into_iter())
+    }
+}
+
+impl<A> Iterator for ProgmemReader<A>
+where
+    A: Iterator<Item = usize>,
+{
+    type Item = Result<u8, SequentialReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(read_progmem)
+    }
+}
+
+#[cfg(feature = "avr-elpm")]
+/** Reader for information memory stored anywhere in program memory (flash),
+yielding one byte for each address produced by `A`.
+
+Only available with the `avr-elpm` feature. Since pointers on AVR are 16 bits,
+the addresses must be obtained by external means, e.g. from linker symbols. */
+pub struct FarProgmemReader<A = Range<u32>>(A);
+
+#[cfg(feature = "avr-elpm")]
+impl<A> FarProgmemReader<A>
+where
+    A: Iterator<Item = u32>,
+{
+    /// Create a reader over 24-bit flash addresses.
+    pub fn new<I>(addrs: I) -> Self
+    where
+        I: IntoIterator<IntoIter = A>,
+    {
+        Self(addrs.into_iter())
+    }
+}
+
+#[cfg(feature = "avr-elpm")]
+impl<A> Iterator for FarProgmemReader<A>
+where
+    A: Iterator<Item = u32>,
+{
+    type Item = Result<u8, SequentialReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(read_progmem_far)
+    }
+}
@@ -0,0 +1,155 @@
+/*! Just enough of an ELF parser to find a section by name in an executable
+image, without pulling in a dependency. */
+
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const ELFDATA2MSB: u8 = 2;
+const SHT_NOBITS: u32 = 8;
+
+/// Field layout of one ELF class and byte order.
+struct Layout {
+    class64: bool,
+    little: bool,
+}
+
+impl Layout {
+    fn uint(&self, image: &[u8], offset: usize, size: usize) -> Option<u64> {
+        let bytes = image.get(offset..offset.checked_add(size)?)?;
+        let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+
+        Some(if self.little {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        })
+    }
+
+    fn half(&self, image: &[u8], offset: usize) -> Option<usize> {
+        self.uint(image, offset, 2).map(|v| v as usize)
+    }
+
+    fn word(&self, image: &[u8], offset: usize) -> Option<u32> {
+        self.uint(image, offset, 4).map(|v| v as u32)
+    }
+
+    // Address or offset sized field.
+    fn addr(&self, image: &[u8], offset: usize) -> Option<usize> {
+        let size = if self.class64 { 8 } else { 4 };
+        self.uint(image, offset, size)?.try_into().ok()
+    }
+}
+
+/// Section header fields that we care about.
+struct SectionHeader {
+    name: usize,
+    kind: u32,
+    offset: usize,
+    size: usize,
+}
+
+fn section_header(image: &[u8], layout: &Layout, at: usize) -> Option<SectionHeader> {
+    // Header fields are at fixed offsets from `at`, which comes from the image.
+    let field = |offset: usize| at.checked_add(offset);
+
+    let (offset, size) = if layout.class64 {
+        (
+            layout.addr(image, field(0x18)?)?,
+            layout.addr(image, field(0x20)?)?,
+        )
+    } else {
+        (
+            layout.addr(image, field(0x10)?)?,
+            layout.addr(image, field(0x14)?)?,
+        )
+    };
+
+    Some(SectionHeader {
+        name: layout.word(image, at)? as usize,
+        kind: layout.word(image, field(4)?)?,
+        offset,
+        size,
+    })
+}
+
+/** Return the contents of the section named `name` in the ELF `image`, or
+`None` if `image` is not a valid ELF file or does not contain the section. */
+pub(crate) fn find_section<'a>(image: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    if image.get(..4)? != b"\x7fELF" {
+        return None;
+    }
+
+    let layout = Layout {
+        class64: match *image.get(4)? {
+            ELFCLASS32 => false,
+            ELFCLASS64 => true,
+            _ => return None,
+        },
+        little: match *image.get(5)? {
+            ELFDATA2LSB => true,
+            ELFDATA2MSB => false,
+            _ => return None,
+        },
+    };
+
+    let (shoff, rest) = if layout.class64 {
+        (layout.addr(image, 0x28)?, 0x3a)
+    } else {
+        (layout.addr(image, 0x20)?, 0x2e)
+    };
+    let shentsize = layout.half(image, rest)?;
+    let shnum = layout.half(image, rest + 2)?;
+    let shstrndx = layout.half(image, rest + 4)?;
+
+    let header = |i: usize| {
+        let at = shoff.checked_add(i.checked_mul(shentsize)?)?;
+        section_header(image, &layout, at)
+    };
+    let strtab = header(shstrndx)?;
+    let names = image.get(strtab.offset..strtab.offset.checked_add(strtab.size)?)?;
+
+    (0..shnum).filter_map(header).find_map(|sh| {
+        let sh_name = names.get(sh.name..)?;
+        let len = sh_name.iter().position(|b| *b == 0)?;
+
+        if &sh_name[..len] != name.as_bytes() || sh.kind == SHT_NOBITS {
+            return None;
+        }
+
+        image.get(sh.offset..sh.offset.checked_add(sh.size)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static THUMBV6M: &[u8] = include_bytes!("../../host/fixtures/thumbv6m.elf");
+
+    #[test]
+    fn find_elf32() {
+        let sect = find_section(THUMBV6M, ".postcard_infomem").unwrap();
+
+        assert_eq!(sect.len(), 0x40);
+        assert!(sect.starts_with(b"PIM\x80"));
+        assert!(find_section(THUMBV6M, ".fw_info").is_none());
+    }
+
+    #[test]
+    fn find_not_elf() {
+        assert!(find_section(b"PIM\x80", ".postcard_infomem").is_none());
+        assert!(find_section(&THUMBV6M[..0x30], ".postcard_infomem").is_none());
+    }
+
+    #[test]
+    fn find_corrupt_offsets() {
+        // ELF64 header whose section headers would lie past the end of the
+        // address space.
+        let mut image = [0; 0x40];
+        image[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        image[0x28..0x30].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+        image[0x3a..0x40].copy_from_slice(&[0x40, 0, 2, 0, 1, 0]);
+
+        assert!(find_section(&image, ".postcard_infomem").is_none());
+    }
+}
@@ -0,0 +1,62 @@
+/*! Access to information memory from the executable image of a running
+program on a hosted target.
+
+`generated_module_name::get()` returns the `static` generated by
+[`include_postcard_infomem`](crate::include_postcard_infomem), which has the
+length and contents known at compile time. If the link section is patched
+after linking (e.g. to grow or re-sign a record), the running program needs to
+read what is actually in its image instead:
+
+* If the linker fragment generated by `HostedConfig` is in use,
+  [`linker_section`](crate::linker_section) returns the whole link section as
+  placed by the linker, which reflects any patches.
+* Otherwise, [`read_exe_section`] reads the link section from the executable
+  file on disk.
+
+Either way, pass the result to [`records`](crate::records).
+*/
+
+extern crate std;
+
+use std::fs;
+use std::io;
+use std::vec::Vec;
+
+use crate::elf;
+
+/** Read the contents of the link section `section` (probably
+`".postcard_infomem"`) from the executable file of the running program, using
+`/proc/self/exe`.
+
+# Errors
+* [`io::Error`]: Returned if reading the executable fails, with
+  [`ErrorKind::NotFound`](io::ErrorKind::NotFound) if the executable does not
+  contain the link section.
+*/
+pub fn read_exe_section(section: &str) -> io::Result<Vec<u8>> {
+    let image = fs::read("/proc/self/exe")?;
+
+    elf::find_section(&image, section)
+        .map(|s| s.to_vec())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "link section not found"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[link_section = ".postcard_infomem_test"]
+    #[used]
+    static TEST_INFOMEM: [u8; 4] = *b"PIM\x80";
+
+    #[test]
+    fn read_own_exe() {
+        let sect = read_exe_section(".postcard_infomem_test").unwrap();
+
+        assert!(sect.windows(4).any(|w| w == b"PIM\x80"));
+        assert_eq!(
+            read_exe_section(".fw_info").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
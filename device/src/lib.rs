@@ -1,8 +1,63 @@
 /*! Helper crate for [`InfoMem`](../postcard_infomem/struct.InfoMem.html) `struct`s
 intended to primarily be used in `no_std` environments (although this does not
 preclude using the crate for hosted applications).
+
+## Libraries
+Library crates can embed their own [`InfoMem`](../postcard_infomem/struct.InfoMem.html)
+alongside the final binary application's by invoking [`include_postcard_infomem`]
+with a unique `generated_module_name` and `symbol`, using a serialized `InfoMem`
+created by the library's own build script. The linker collects every `InfoMem` into
+the same link section, and [`records`] iterates over all of them at runtime.
+
+However, a library's `static` lives in an object file of the library's `rlib`
+archive, and the linker only includes that object file if the binary
+references a symbol defined in it. The binary must therefore call
+`generated_module_name::keep()` for each library `InfoMem`, e.g. through a
+function that the library exports for this purpose:
+
+```ignore
+// In the library:
+# use postcard_infomem_device::include_postcard_infomem;
+include_postcard_infomem!(concat!(env!("OUT_DIR"), "/info.bin"), mylib_infomem, symbol = "MYLIB_INFOMEM");
+
+pub fn keep_infomem() {
+    mylib_infomem::keep();
+}
+```
+
+```ignore
+// In the binary:
+# use postcard_infomem_device::{linker_section, records};
+mylib::keep_infomem();
+
+for im in records(linker_section!()) {
+    // Print im.app.name, etc.
+}
+```
 */
 #![no_std]
+#![cfg_attr(target_arch = "avr", feature(asm_experimental_arch))]
+
+use postcard_infomem::{iter_from_bytes_magic, IterMagic};
+
+#[cfg(feature = "macros")]
+pub use postcard_infomem_macros::postcard_infomem;
+
+#[cfg(target_arch = "avr")]
+pub mod avr;
+
+#[cfg(any(test, all(feature = "std", target_os = "linux")))]
+mod elf;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod hosted;
+
+#[doc(hidden)]
+/// Items used by code generated by [`include_postcard_infomem`].
+pub mod __private {
+    pub use postcard::Result;
+    pub use postcard_infomem::{from_bytes_magic, from_seq_magic, InfoMem, SequentialReadError};
+}
 
 #[macro_export]
 /** Create a `static` variable to hold a serialized [`InfoMem`](../postcard_infomem/struct.InfoMem.html) structure.
@@ -20,6 +75,54 @@ This macro can be invoked in one of two ways:
 
 If `generated_module_name` is omitted, it defaults to `infomem`.
 
+## Options
+When `generated_module_name` is given, it may be followed by a comma-separated
+list of `key = value` options:
+
+```ignore
+# use postcard_infomem_device::include_postcard_infomem;
+include_postcard_infomem!("/path/to/binary/infomem/file", infomem, capacity = 256);
+```
+
+* `capacity`: Reserve `capacity` bytes for the generated `static`, instead of
+  exactly the size of the binary file. The serialized [`InfoMem`](../postcard_infomem/struct.InfoMem.html)
+  is placed at the start of the reservation, and the remaining bytes are
+  padded with the `fill` byte. This leaves room to patch or grow the record
+  after the application has been built. It is a compile-time error if the
+  binary file is larger than `capacity`. `generated_module_name::get()` returns
+  the entire reservation, including padding.
+* `fill`: Byte used to pad the reservation when `capacity` is given. Defaults
+  to `0xFF`, which matches the erased state of most flash memories.
+* `symbol`: Linker symbol name of the generated `static`, as a string literal.
+  Defaults to `"INFOMEM"`.
+* `section`: Link section of the generated `static`, as a string literal.
+  Defaults to `".postcard_infomem"` (or `".pim"` on Windows, `"__DATA,__pim"`
+  on Apple targets, and depends on `memory` on AVR). See
+  [Linker Considerations](#linker-considerations).
+* `memory`: Memory to place the generated `static` in on AVR; either `eeprom`
+  (the default) or `progmem`. Ignored on all other targets. See
+  [Linker Considerations](#linker-considerations).
+
+Values must be a single token; wrap more complicated expressions in
+parentheses, e.g. `capacity = (4 * 64)`. Each option may be given at most once.
+
+## Multiple Information Memories
+This macro can be invoked more than once per binary, e.g. to embed separate
+records for a bootloader, an application, and libraries. Each invocation needs
+its own `generated_module_name` and `symbol`:
+
+```ignore
+# use postcard_infomem_device::include_postcard_infomem;
+include_postcard_infomem!("/path/to/boot/infomem/file", boot_infomem, symbol = "BOOT_INFOMEM");
+include_postcard_infomem!("/path/to/app/infomem/file", app_infomem, symbol = "APP_INFOMEM");
+```
+
+All generated `static`s are placed in the same link section, so the linker
+fragments generated by [`postcard-infomem-host`](../postcard-infomem-host/index.html)
+keep every one of them. Since each serialized [`InfoMem`](../postcard_infomem/struct.InfoMem.html)
+starts with a magic header, the records can be found again by scanning the
+section, e.g. with [`iter_from_bytes_magic`](../postcard_infomem/fn.iter_from_bytes_magic.html).
+
 On [Harvard architectures](https://en.wikipedia.org/wiki/Harvard_architecture)
 like AVR, information memory may be stored in a separate address space. Accessing
 information memory as if it was in the same address space for program data would
@@ -56,11 +159,44 @@ for a in addrs {
 }
 ```
 
+To deserialize the `static` without writing target-specific code, use
+`generated_module_name::read()`. On targets with a single address space, `read()`
+borrows directly from the `static` and ignores `buf`. On AVR, `read()` reads
+information memory byte-by-byte using `generated_module_name::reader()`, and
+copies any borrowed data into `buf`, which must be large enough to hold all
+strings and the user payload:
+
+```ignore
+# use postcard_infomem_device::include_postcard_infomem;
+#
+include_postcard_infomem!("/path/to/binary/infomem/file");
+
+let mut buf = [0u8; 128];
+let im: InfoMem = infomem::read(&mut buf).unwrap();
+```
+
 ## Linker Considerations.
 The generated `static` variable is annotated with the [`link_section` attribute](https://doc.rust-lang.org/reference/abi.html#the-link_section-attribute).
-Currently, on all targets except the AVR, the link section is named `.postcard_infomem`.
+Currently, on most targets, the link section is named `.postcard_infomem`.
+On Windows, the link section is named `.pim`, since section names in PE images
+are limited to 8 characters. On Apple targets, the link section is named
+`__DATA,__pim`, since Mach-O sections must be qualified by a segment name.
 On AVR, the link section is named `.eeprom`; _the `avr-gcc` toolchain has special
-logic to place sections named `.eeprom` into EEPROM memory._
+logic to place sections named `.eeprom` into EEPROM memory._ Since many AVRs have
+very little EEPROM, the `memory = progmem` option instead names the link section
+`.progmem.data`, which `avr-gcc` places into flash alongside other read-only
+data. Either way, `reader()` reads the appropriate memory (using `lpm`
+instructions for flash). The readers themselves are available in the `avr`
+module.
+
+The `section` option overrides the name of the link section on all targets,
+e.g. to match a region that a linker script or vendor SDK already reserves.
+Section names should consist of a `.` followed by characters valid in a Rust
+identifier, so that the `_s`/`_e` symbols delimiting the section can be passed
+to [`linker_section`]. Pass the same name to the `set_section_name` method of the
+[`postcard-infomem-host`](../postcard-infomem-host/index.html) linker fragment
+configs. On AVR, the `memory` option still decides how `reader()` reads the
+section.
 
 This macro also annotates the `static` variable with the [`used` attribute](https://doc.rust-lang.org/reference/abi.html#the-used-attribute)
 so that `rustc` knows not to optimize the variable away if your application
@@ -68,6 +204,10 @@ never reads from it. However, linkers _also_ have a tendency to [garbage-collect
 unused symbols unless told not to. The [`postcard-infomem-host`](../postcard-infomem-host/index.html) crate provides
 functions for build scripts to automate generating these linker fragments for you.
 
+With the `macros` feature enabled, the [`postcard_infomem`](macro.postcard_infomem.html)
+procedural macro can generate the serialized `InfoMem` at compile time instead,
+without requiring a build script.
+
 Complete/working examples of using this macro based on the above can be found in
 the `examples` directory/[crate](https://github.com/cr1901/postcard-infomem/tree/main/examples)
 of this workspace.
@@ -77,27 +217,74 @@ macro_rules! include_postcard_infomem {
         include_postcard_infomem!($pim, infomem);
     };
 
-    ($pim:expr, $mod:ident) => {
+    ($pim:expr, $mod:ident $(, $key:ident = $val:tt)* $(,)?) => {
+        $crate::__postcard_infomem_static!(include_bytes!($pim), $mod $(, $key = $val)*);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+/** Implementation of [`include_postcard_infomem`], where `$data` is an expression
+of type `&[u8; N]` containing a serialized [`InfoMem`](../postcard_infomem/struct.InfoMem.html).
+
+Shared with the `postcard_infomem` procedural macro, which passes a byte string
+literal instead of including a file. */
+macro_rules! __postcard_infomem_static {
+    ($data:expr, $mod:ident $(, $key:ident = $val:tt)*) => {
         /* AVR stores EEPROM in a separate address space. Access the variable
         INFOMEM from code will try to access at the same offset in a
         different address space. This is a spatial memory-safety violation.
         Avoid the problem by not allowing users to access the variable
         directly.
 
-        We turn on no_mangle because multiple INFOMEMs are not
-        supported at this time. */
+        We export the variable under a known symbol name (INFOMEM, unless
+        overridden) so that it can be found by external tools. Multiple
+        INFOMEMs require distinct symbol names. */
         pub mod $mod {
+            $crate::__check_options!($($key = $val),*);
+
+            // Either the exact size of the serialized data, or the size the user
+            // asked to reserve. Values may be parenthesized, as documented.
+            #[allow(unused_parens)]
+            const INFOMEM_LEN: usize =
+                $crate::__option!(capacity, $data.len(); $($key = $val),*);
+
             #[cfg(not(doctest))]
-            #[cfg_attr(target_arch = "avr", link_section = ".eeprom")]
-            #[cfg_attr(not(target_arch = "avr"), link_section = ".postcard_infomem")]
-            #[no_mangle]
+            #[cfg_attr(
+                target_arch = "avr",
+                link_section = $crate::__option!(
+                    section,
+                    $crate::__avr_section!($($key = $val),*);
+                    $($key = $val),*
+                )
+            )]
+            #[cfg_attr(
+                not(target_arch = "avr"),
+                link_section = $crate::__option!(
+                    section,
+                    $crate::__default_section!();
+                    $($key = $val),*
+                )
+            )]
+            #[export_name = $crate::__option!(symbol, "INFOMEM"; $($key = $val),*)]
             #[used]
-            static INFOMEM: [u8; include_bytes!($pim).len()] = *include_bytes!($pim);
+            #[allow(unused_parens)]
+            static INFOMEM: [u8; INFOMEM_LEN] =
+                $crate::pad($data, $crate::__option!(fill, 0xff; $($key = $val),*));
 
             // Doesn't seem to work...
             #[cfg(doctest)]
             static INFOMEM: [u8; 7] = b"doctest";
 
+            /// Reference the `INFOMEM` `static`, so that the linker includes
+            /// it in the final binary even if this module lives in a library
+            /// crate. See the "Libraries" section of the
+            /// `postcard_infomem_device` documentation.
+            #[inline(never)]
+            pub fn keep() {
+                core::hint::black_box(&INFOMEM as *const _);
+            }
+
             #[doc = "Access information memory safely, depending on target.\
             \n\
             This can be used as a portable entry point to access the `INFOMEM`
@@ -119,9 +306,356 @@ macro_rules! include_postcard_infomem {
                     end: INFOMEM.as_ptr() as usize + INFOMEM.len(),
                 }
             }
+
+            /// Deserialize information memory, depending on target.
+            #[cfg(not(target_arch = "avr"))]
+            pub fn read<'buf>(
+                _buf: &'buf mut [u8],
+            ) -> $crate::__private::Result<$crate::__private::InfoMem<'buf>> {
+                $crate::__private::from_bytes_magic(get())
+            }
+
+            /// Read information memory byte-by-byte from EEPROM or flash.
+            #[cfg(target_arch = "avr")]
+            pub fn reader() -> impl Iterator<Item = Result<u8, $crate::__private::SequentialReadError>>
+            {
+                get().map($crate::__avr_reader!($($key = $val),*))
+            }
+
+            /// Deserialize information memory, depending on target.
+            #[cfg(target_arch = "avr")]
+            pub fn read<'buf>(
+                buf: &'buf mut [u8],
+            ) -> $crate::__private::Result<$crate::__private::InfoMem<'buf>> {
+                $crate::__private::from_seq_magic(reader(), buf)
+            }
         }
     };
 }
 
+#[macro_export]
+/** Return a `&'static [u8]` of the entire link section containing
+[`InfoMem`](../postcard_infomem/struct.InfoMem.html)s,
+as delimited by a pair of linker symbols.
+
+This macro can be invoked in one of two ways:
+
+* ```ignore
+  # use postcard_infomem_device::linker_section;
+  let sect: &'static [u8] = linker_section!();
+  ```
+* ```ignore
+  # use postcard_infomem_device::linker_section;
+  let sect: &'static [u8] = linker_section!(start_symbol, end_symbol);
+  ```
+
+If the symbols are omitted, they default to `_spostcard_infomem` and
+`_epostcard_infomem` (`_spim` and `_epim` on Windows), which are defined by the
+linker fragments that [`postcard-infomem-host`](../postcard-infomem-host/index.html)
+generates. On Apple targets, the Mach-O linker defines symbols delimiting the
+`__DATA,__pim` section itself, so no linker fragment is required.
+If the link section was renamed (e.g. to `.fw_info`), the symbols are named
+after the section instead (e.g. `_sfw_info` and `_efw_info`).
+The symbols are only referenced when this macro is invoked; linking will
+fail if they are not defined.
+
+This macro is not supported on targets where the link section lives in a
+separate address space (e.g. EEPROM on AVR).
+*/
+macro_rules! linker_section {
+    () => {{
+        #[cfg(target_vendor = "apple")]
+        let sect = {
+            extern "C" {
+                #[link_name = "\u{1}section$start$__DATA$__pim"]
+                static START: u8;
+                #[link_name = "\u{1}section$end$__DATA$__pim"]
+                static END: u8;
+            }
+
+            // SAFETY: The linker defines these symbols at the boundaries of
+            // the section, which is never written to.
+            unsafe {
+                $crate::from_linker_symbols(
+                    ::core::ptr::addr_of!(START),
+                    ::core::ptr::addr_of!(END),
+                )
+            }
+        };
+        #[cfg(windows)]
+        let sect = $crate::linker_section!(_spim, _epim);
+        #[cfg(not(any(target_vendor = "apple", windows)))]
+        let sect = $crate::linker_section!(_spostcard_infomem, _epostcard_infomem);
+        sect
+    }};
+
+    ($start:ident, $end:ident) => {{
+        #[allow(non_upper_case_globals)]
+        extern "C" {
+            static $start: u8;
+            static $end: u8;
+        }
+
+        // SAFETY: The linker places the start and end symbols at the
+        // boundaries of the link section, which is never written to.
+        unsafe {
+            $crate::from_linker_symbols(::core::ptr::addr_of!($start), ::core::ptr::addr_of!($end))
+        }
+    }};
+}
+
+/** Create a `&'static [u8]` from the addresses of two linker symbols.
+
+Prefer the [`linker_section`] macro over calling this function directly.
+
+# Safety
+`start` and `end` must delimit a region of memory in the same address space as
+program data, which remains valid and unmodified for the rest of the program,
+with `start <= end`.
+*/
+pub unsafe fn from_linker_symbols(start: *const u8, end: *const u8) -> &'static [u8] {
+    core::slice::from_raw_parts(start, end as usize - start as usize)
+}
+
+/** Iterate over every [`InfoMem`](../postcard_infomem/struct.InfoMem.html)
+contained in a link section, e.g. one each for the final binary application and
+any libraries it links against.
+
+Any padding between `InfoMem`s is skipped; see [`IterMagic`] for details.
+`section` is likely the return value of [`linker_section`], or on hosted
+targets, of `hosted::read_exe_section` (with the `std` feature). */
+pub fn records(section: &[u8]) -> IterMagic<'_, &[u8]> {
+    iter_from_bytes_magic(section)
+}
+
+#[doc(hidden)]
+#[macro_export]
+/** Look up the value of option `$want` in a list of `key = value` options
+passed to [`include_postcard_infomem`], or return `$default` if not present. */
+macro_rules! __option {
+    ($want:ident, $default:expr;) => {
+        $default
+    };
+
+    (capacity, $default:expr; capacity = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $val
+    };
+
+    (fill, $default:expr; fill = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $val
+    };
+
+    (symbol, $default:expr; symbol = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $val
+    };
+
+    (section, $default:expr; section = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $val
+    };
+
+    ($want:ident, $default:expr; $skip:ident = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__option!($want, $default; $($key = $rest),*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+/// Reject options (or values) that [`include_postcard_infomem`] does not know about.
+macro_rules! __check_options {
+    () => {};
+
+    (capacity = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__check_duplicate!(capacity; $($key = $rest),*);
+        $crate::__check_options!($($key = $rest),*);
+    };
+
+    (fill = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__check_duplicate!(fill; $($key = $rest),*);
+        $crate::__check_options!($($key = $rest),*);
+    };
+
+    (symbol = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__check_duplicate!(symbol; $($key = $rest),*);
+        $crate::__check_options!($($key = $rest),*);
+    };
+
+    (section = $val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__check_duplicate!(section; $($key = $rest),*);
+        $crate::__check_options!($($key = $rest),*);
+    };
+
+    (memory = eeprom $(, $key:ident = $rest:tt)*) => {
+        $crate::__check_duplicate!(memory; $($key = $rest),*);
+        $crate::__check_options!($($key = $rest),*);
+    };
+
+    (memory = progmem $(, $key:ident = $rest:tt)*) => {
+        $crate::__check_duplicate!(memory; $($key = $rest),*);
+        $crate::__check_options!($($key = $rest),*);
+    };
+
+    (memory = $other:tt $(, $key:ident = $rest:tt)*) => {
+        compile_error!(concat!(
+            "memory must be one of eeprom or progmem, not ",
+            stringify!($other)
+        ));
+    };
+
+    ($other:ident = $val:tt $(, $key:ident = $rest:tt)*) => {
+        compile_error!(concat!(
+            "unknown include_postcard_infomem option: ",
+            stringify!($other)
+        ));
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+/** Reject option `$want` if it appears again in the remaining options passed to
+[`include_postcard_infomem`]. */
+macro_rules! __check_duplicate {
+    ($want:ident;) => {};
+
+    (capacity; capacity = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        compile_error!("duplicate include_postcard_infomem option: capacity");
+    };
+
+    (fill; fill = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        compile_error!("duplicate include_postcard_infomem option: fill");
+    };
+
+    (symbol; symbol = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        compile_error!("duplicate include_postcard_infomem option: symbol");
+    };
+
+    (section; section = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        compile_error!("duplicate include_postcard_infomem option: section");
+    };
+
+    (memory; memory = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        compile_error!("duplicate include_postcard_infomem option: memory");
+    };
+
+    ($want:ident; $skip:ident = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__check_duplicate!($want; $($key = $rest),*);
+    };
+}
+
+#[cfg(target_vendor = "apple")]
+#[doc(hidden)]
+#[macro_export]
+/// Default link section name of [`include_postcard_infomem`] on Mach-O targets.
+macro_rules! __default_section {
+    () => {
+        "__DATA,__pim"
+    };
+}
+
+#[cfg(windows)]
+#[doc(hidden)]
+#[macro_export]
+/// Default link section name of [`include_postcard_infomem`] on PE targets.
+macro_rules! __default_section {
+    () => {
+        ".pim"
+    };
+}
+
+#[cfg(not(any(target_vendor = "apple", windows)))]
+#[doc(hidden)]
+#[macro_export]
+/// Default link section name of [`include_postcard_infomem`] on ELF targets.
+macro_rules! __default_section {
+    () => {
+        ".postcard_infomem"
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+/// Link section name on AVR for the `memory` option of [`include_postcard_infomem`].
+macro_rules! __avr_section {
+    () => {
+        ".eeprom"
+    };
+
+    (memory = eeprom $(, $key:ident = $rest:tt)*) => {
+        ".eeprom"
+    };
+
+    (memory = progmem $(, $key:ident = $rest:tt)*) => {
+        ".progmem.data"
+    };
+
+    ($skip:ident = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__avr_section!($($key = $rest),*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+/// Byte reader on AVR for the `memory` option of [`include_postcard_infomem`].
+macro_rules! __avr_reader {
+    () => {
+        $crate::avr::read_eeprom
+    };
+
+    (memory = eeprom $(, $key:ident = $rest:tt)*) => {
+        $crate::avr::read_eeprom
+    };
+
+    (memory = progmem $(, $key:ident = $rest:tt)*) => {
+        $crate::avr::read_progmem
+    };
+
+    ($skip:ident = $_val:tt $(, $key:ident = $rest:tt)*) => {
+        $crate::__avr_reader!($($key = $rest),*)
+    };
+}
+
+#[doc(hidden)]
+/** Copy a serialized [`InfoMem`](../postcard_infomem/struct.InfoMem.html) into
+a buffer of `N` bytes, padding the remaining bytes with `fill`.
+
+Intended to be evaluated in a `const` context by [`include_postcard_infomem`];
+panicking here aborts compilation. */
+pub const fn pad<const N: usize>(src: &[u8], fill: u8) -> [u8; N] {
+    assert!(
+        src.len() <= N,
+        "serialized InfoMem does not fit in the reserved capacity"
+    );
+
+    let mut buf = [fill; N];
+    let mut i = 0;
+    while i < src.len() {
+        buf[i] = src[i];
+        i += 1;
+    }
+
+    buf
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_exact() {
+        let padded: [u8; 4] = pad(b"PIM\x80", 0xff);
+
+        assert_eq!(&padded, b"PIM\x80");
+    }
+
+    #[test]
+    fn pad_fill() {
+        const PADDED: [u8; 8] = pad(b"PIM\x80", 0xff);
+
+        assert_eq!(&PADDED, b"PIM\x80\xff\xff\xff\xff");
+    }
+
+    #[test]
+    #[should_panic]
+    fn pad_too_small() {
+        let _: [u8; 2] = pad(b"PIM\x80", 0xff);
+    }
+}
@@ -0,0 +1,15 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use postcard_infomem::{to_allocvec_magic, InfoMem};
+
+fn main() {
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+
+    let mut im: InfoMem = InfoMem::default();
+    im.app.name = Some("fixture-lib".into());
+    fs::write(out.join("info.bin"), to_allocvec_magic(&im).unwrap()).unwrap();
+
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+use postcard_infomem_device::include_postcard_infomem;
+
+include_postcard_infomem!(
+    concat!(env!("OUT_DIR"), "/info.bin"),
+    fixture_infomem,
+    symbol = "FIXTURE_LIB_INFOMEM"
+);
+
+/// Keep this library's InfoMem in the final binary.
+pub fn keep_infomem() {
+    fixture_infomem::keep();
+}
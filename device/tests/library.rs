@@ -0,0 +1,17 @@
+#![cfg(all(feature = "std", target_os = "linux"))]
+
+use postcard_infomem_device::hosted::read_exe_section;
+use postcard_infomem_device::records;
+
+#[test]
+fn record_from_library() {
+    postcard_infomem_fixture_lib::keep_infomem();
+
+    let sect = read_exe_section(".postcard_infomem").unwrap();
+    let names: Vec<_> = records(&sect)
+        .filter_map(|im| im.app.name)
+        .map(|name| name.as_str().to_owned())
+        .collect();
+
+    assert_eq!(names, ["fixture-lib"]);
+}
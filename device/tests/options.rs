@@ -0,0 +1,16 @@
+#![deny(unused_parens)]
+
+postcard_infomem_device::__postcard_infomem_static!(
+    b"PIM\x80",
+    options_infomem,
+    capacity = (4 * 4),
+    fill = (0x0f),
+    symbol = "OPTIONS_INFOMEM"
+);
+
+#[test]
+fn parenthesized_options() {
+    assert_eq!(options_infomem::get().len(), 16);
+    assert_eq!(&options_infomem::get()[..4], b"PIM\x80");
+    assert!(options_infomem::get()[4..].iter().all(|&b| b == 0x0f));
+}
@@ -53,7 +53,7 @@ fn main() {
 
     write!(w, "\r\n\r\nDeserializing infomem... ").unwrap();
 
-    match deserialize_infomem(infomem::get(), &mut buf) {
+    match infomem::read(&mut buf) {
         Ok(_im) => {
             write!(w, "Okay!\r\n").unwrap();
         }
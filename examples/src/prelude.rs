@@ -8,9 +8,7 @@ pub use core::ops;
 #[allow(unused_imports)]
 pub use postcard::de_flavors::Flavor;
 #[allow(unused_imports)]
-pub use postcard_infomem::{
-    de::Seq, from_bytes_magic, from_seq_magic, InfoMem, SequentialReadError,
-};
+pub use postcard_infomem::{de::Seq, SequentialReadError};
 pub use postcard_infomem_device::*;
 
 /// Imports dependent on No OS (`target_os=none` or `unknown`) vs OS.
@@ -57,7 +55,7 @@ pub mod hal {
             pub use core::ops::Range;
 
             pub use ruduino;
-            pub use ruduino::cores::current::{port, EEAR, EECR, EEDR};
+            pub use ruduino::cores::current::port;
             pub use ruduino::legacy::serial;
             pub use ruduino::Pin;
             pub use ruduino::Register;
@@ -0,0 +1,10 @@
+    .syntax unified
+    .thumb
+    .text
+    .rept 0x20
+    nop
+    .endr
+    .section .postcard_infomem,"a"
+    .byte 0x50, 0x49, 0x4d, 0x80, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x66, 0x69, 0x78, 0x74, 0x75, 0x72, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+    .byte 0x50, 0x49, 0x48, 0x80
+    .fill 0x20, 1, 0x00
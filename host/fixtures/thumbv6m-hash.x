@@ -0,0 +1,14 @@
+MEMORY {
+    FLASH : ORIGIN = 0x10000000, LENGTH = 0x1000
+    INFOMEM : ORIGIN = 0x10001000, LENGTH = 0x100
+}
+SECTIONS {
+    .text : {
+        KEEP(*(.text))
+    } > FLASH
+    .postcard_infomem : {
+        _spostcard_infomem = .;
+        KEEP(*(.postcard_infomem))
+        _epostcard_infomem = .;
+    } > INFOMEM
+}
@@ -0,0 +1,3 @@
+    .section .postcard_infomem,"a"
+    .byte 0x50, 0x49, 0x4d, 0x80, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x66, 0x69, 0x78, 0x74, 0x75, 0x72, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+    .fill 0x25, 1, 0xff
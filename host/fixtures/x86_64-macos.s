@@ -0,0 +1,6 @@
+    .section __TEXT,__text,regular,pure_instructions
+    .globl _main
+_main:
+    retq
+    .section __DATA,__pim
+    .byte 0x50, 0x49, 0x4d, 0x80, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x66, 0x69, 0x78, 0x74, 0x75, 0x72, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
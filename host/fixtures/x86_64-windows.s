@@ -0,0 +1,6 @@
+    .text
+    .globl _start
+_start:
+    ret
+    .section .pim,"dr"
+    .byte 0x50, 0x49, 0x4d, 0x80, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x66, 0x69, 0x78, 0x74, 0x75, 0x72, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
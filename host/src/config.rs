@@ -0,0 +1,543 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use postcard_infomem::{Channel, InfoStr, Semver};
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::ldscript::BareConfig;
+use crate::*;
+
+/// Name of the standalone configuration file in `CARGO_MANIFEST_DIR`.
+const CONFIG_FILE: &str = "postcard-infomem.toml";
+
+/** Declarative configuration for [`build_from_config`].
+
+The configuration is read from `postcard-infomem.toml` in
+`CARGO_MANIFEST_DIR`. If that file does not exist, the
+`[package.metadata.postcard-infomem]` table of `Cargo.toml` is used instead.
+If neither exists, the [default](BuildConfig::default) configuration is used.
+All tables and keys are optional:
+
+```toml
+# Fields populated by generate_from_env, all true by default.
+[env]
+app-date = false
+rustc-git = false
+
+# Static values which override the generated ones, in the format written by
+# to_toml.
+[app]
+name = "my-firmware"
+version = "1.2.0-rc.1"
+
+[rustc]
+channel = "stable"
+
+# User payload, relative to CARGO_MANIFEST_DIR.
+[user]
+file = "calibration.bin"
+
+# Serialized InfoMem, relative to OUT_DIR.
+[output]
+file = "info.bin"
+header = true
+image-hash = false
+
+# Linker fragment, relative to OUT_DIR. "type" is one of "hosted",
+# "bare-section", or "bare-append". The remaining keys correspond to the
+# setters of the respective config structs.
+[ldscript]
+type = "bare-section"
+file = "info.x"
+region = "FLASH"
+region-offset = 0x100
+```
+*/
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildConfig<'a> {
+    env: EnvFields,
+    #[serde(borrow)]
+    app: AppFields<'a>,
+    #[serde(borrow)]
+    rustc: RustcFields<'a>,
+    user: Option<UserFile>,
+    output: Output,
+    ldscript: Option<LdScript>,
+}
+
+/// Flags of [`EnvConfig`], as read from the `[env]` table.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct EnvFields {
+    app_name: bool,
+    app_version: bool,
+    app_git: bool,
+    app_date: bool,
+    rustc_version: bool,
+    rustc_llvm: bool,
+    rustc_git: bool,
+    rustc_host: bool,
+    rustc_channel: bool,
+}
+
+impl Default for EnvFields {
+    /// Populate all [`InfoMem`] fields, like [`EnvConfig::default`].
+    fn default() -> Self {
+        Self {
+            app_name: true,
+            app_version: true,
+            app_git: true,
+            app_date: true,
+            rustc_version: true,
+            rustc_llvm: true,
+            rustc_git: true,
+            rustc_host: true,
+            rustc_channel: true,
+        }
+    }
+}
+
+impl From<&EnvFields> for EnvConfig {
+    fn from(value: &EnvFields) -> Self {
+        EnvConfig::none()
+            .set_app_name(value.app_name)
+            .set_app_version(value.app_version)
+            .set_app_git(value.app_git)
+            .set_app_date(value.app_date)
+            .set_rustc_version(value.rustc_version)
+            .set_rustc_llvm(value.rustc_llvm)
+            .set_rustc_git(value.rustc_git)
+            .set_rustc_host(value.rustc_host)
+            .set_rustc_channel(value.rustc_channel)
+    }
+}
+
+/** Overrides of [`AppInfo`](postcard_infomem::AppInfo), as read from the
+`[app]` table.
+
+Unlike [`AppInfo`](postcard_infomem::AppInfo) itself, unknown keys are
+rejected, so that a misspelled key is not silently ignored. */
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AppFields<'a> {
+    #[serde(borrow)]
+    name: Option<InfoStr<'a>>,
+    #[serde(borrow)]
+    version: Option<Semver<'a>>,
+    #[serde(borrow)]
+    git: Option<InfoStr<'a>>,
+    #[serde(with = "postcard_infomem::date")]
+    build_date: Option<OffsetDateTime>,
+}
+
+/** Overrides of [`RustcInfo`](postcard_infomem::RustcInfo), as read from the
+`[rustc]` table. See [`AppFields`]. */
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RustcFields<'a> {
+    #[serde(borrow)]
+    version: Option<Semver<'a>>,
+    #[serde(borrow)]
+    llvm_version: Option<Semver<'a>>,
+    channel: Option<Channel>,
+    #[serde(borrow)]
+    git: Option<InfoStr<'a>>,
+    #[serde(borrow)]
+    host: Option<InfoStr<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserFile {
+    file: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct Output {
+    file: String,
+    header: bool,
+    image_hash: bool,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            file: "info.bin".into(),
+            header: true,
+            image_hash: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum LdScriptType {
+    Hosted,
+    BareSection,
+    BareAppend,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct LdScript {
+    #[serde(rename = "type")]
+    kind: LdScriptType,
+    #[serde(default = "LdScript::default_file")]
+    file: String,
+    section: Option<String>,
+    region: Option<String>,
+    address: Option<u64>,
+    region_offset: Option<u64>,
+    insert_before: Option<String>,
+    insert_after: Option<String>,
+    alignment: Option<u64>,
+    fill: Option<u8>,
+}
+
+impl LdScript {
+    fn default_file() -> String {
+        "info.x".into()
+    }
+
+    /// Apply the options shared by the bare-metal linker script types.
+    fn bare<'a>(&'a self, mut cfg: BareConfig<'a>) -> BareConfig<'a> {
+        if let Some(section) = &self.section {
+            cfg = cfg.set_section_name(section);
+        }
+        if let Some(region) = &self.region {
+            cfg = cfg.set_memory_region(region);
+        }
+        if let Some(addr) = self.address {
+            cfg = cfg.set_address(addr);
+        }
+        if let Some(offset) = self.region_offset {
+            cfg = cfg.set_region_offset(offset);
+        }
+        if let Some(out_section) = &self.insert_before {
+            cfg = cfg.set_insert_before(out_section);
+        }
+        if let Some(out_section) = &self.insert_after {
+            cfg = cfg.set_insert_after(out_section);
+        }
+        if let Some(align) = self.alignment {
+            cfg = cfg.set_alignment(align);
+        }
+        if let Some(fill) = self.fill {
+            cfg = cfg.set_fill(fill);
+        }
+        cfg
+    }
+
+    fn generate(&self, out_dir: &Path) -> Result<(), Box<dyn Error>> {
+        if self.address.is_some() && self.region_offset.is_some() {
+            return Err("ldscript: address and region-offset are mutually exclusive".into());
+        }
+
+        if self.insert_before.is_some() && self.insert_after.is_some() {
+            return Err("ldscript: insert-before and insert-after are mutually exclusive".into());
+        }
+
+        let path = out_dir.join(&self.file);
+
+        match self.kind {
+            LdScriptType::Hosted => {
+                if self.region.is_some()
+                    || self.address.is_some()
+                    || self.region_offset.is_some()
+                    || self.insert_before.is_some()
+                    || self.insert_after.is_some()
+                    || self.alignment.is_some()
+                    || self.fill.is_some()
+                {
+                    return Err("ldscript: type \"hosted\" only supports section".into());
+                }
+
+                let mut cfg = HostedConfig::default();
+                if let Some(section) = &self.section {
+                    cfg = cfg.set_section_name(section);
+                }
+                generate_infomem_ldscript(path, cfg)
+            }
+            LdScriptType::BareSection => {
+                let cfg = BareSectionConfig::default();
+                generate_infomem_ldscript(path, BareSectionConfig(self.bare(cfg.0)))
+            }
+            LdScriptType::BareAppend => {
+                let cfg = BareAppendConfig::default();
+                generate_infomem_ldscript(path, BareAppendConfig(self.bare(cfg.0)))
+            }
+        }
+    }
+}
+
+/** Tell Cargo to rerun the build script if `path` changes. Nothing is printed
+outside of build scripts, i.e. if `OUT_DIR` is not set. */
+fn rerun_if_changed(path: &Path) {
+    if env::var_os("OUT_DIR").is_some() {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+}
+
+/** Read the [`BuildConfig`] of the crate in `manifest_dir`.
+
+See [`BuildConfig`] for where the configuration is read from. When called
+from a build script, this function tells Cargo to rerun it if the file the
+configuration was read from changes.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`io::Error`](std::io::Error): Returned if reading a configuration file fails.
+* [`toml::de::Error`]: Returned if parsing a configuration file fails.
+*/
+pub fn read_build_config<P>(manifest_dir: P) -> Result<BuildConfig<'static>, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let path = manifest_dir.as_ref().join(CONFIG_FILE);
+    if path.exists() {
+        rerun_if_changed(&path);
+        let s = fs::read_to_string(&path)?;
+        return Ok(BuildConfig::deserialize(toml::Deserializer::new(&s))?);
+    }
+
+    let path = manifest_dir.as_ref().join("Cargo.toml");
+    if path.exists() {
+        rerun_if_changed(&path);
+        let mut manifest: toml::Table = fs::read_to_string(&path)?.parse()?;
+        let table = manifest
+            .remove("package")
+            .and_then(|mut p| p.as_table_mut()?.remove("metadata"))
+            .and_then(|mut m| m.as_table_mut()?.remove("postcard-infomem"));
+
+        if let Some(table) = table {
+            return Ok(BuildConfig::deserialize(table)?);
+        }
+    }
+
+    Ok(BuildConfig::default())
+}
+
+/** Generate and write out an [`InfoMem`], and optionally a linker fragment,
+as described by `cfg`.
+
+The [`InfoMem`] is populated with [`generate_from_env`] according to the
+`[env]` table, after which the values of the `[app]` and `[rustc]` tables (if
+any) override the generated values. The user payload file is read from
+`manifest_dir`, and Cargo is told to rerun the build script if it changes. The
+outputs are written to `out_dir` with [`write_info_to_file`] and
+[`generate_infomem_ldscript`].
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include
+those returned by [`generate_from_env`], [`write_info_to_file`], and
+[`generate_infomem_ldscript`], as well as:
+* [`io::Error`](std::io::Error): Returned if reading the user payload file fails.
+
+An error is also returned if the `[ldscript]` table contains conflicting or
+unsupported keys.
+*/
+pub fn run_build_config<P, Q>(
+    cfg: &BuildConfig,
+    manifest_dir: P,
+    out_dir: Q,
+) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let mut im = generate_from_env((&cfg.env).into())?;
+
+    let app = &cfg.app;
+    if let Some(name) = &app.name {
+        im.app.name = Some(name.as_borrowed());
+    }
+    if let Some(version) = &app.version {
+        im.app.version = Some(version.as_borrowed());
+    }
+    if let Some(git) = &app.git {
+        im.app.git = Some(git.as_borrowed());
+    }
+    if app.build_date.is_some() {
+        im.app.build_date = app.build_date;
+    }
+
+    let rustc = &cfg.rustc;
+    if let Some(version) = &rustc.version {
+        im.rustc.version = Some(version.as_borrowed());
+    }
+    if let Some(llvm_version) = &rustc.llvm_version {
+        im.rustc.llvm_version = Some(llvm_version.as_borrowed());
+    }
+    if rustc.channel.is_some() {
+        im.rustc.channel = rustc.channel;
+    }
+    if let Some(git) = &rustc.git {
+        im.rustc.git = Some(git.as_borrowed());
+    }
+    if let Some(host) = &rustc.host {
+        im.rustc.host = Some(host.as_borrowed());
+    }
+
+    let user = match &cfg.user {
+        Some(u) => {
+            let path = manifest_dir.as_ref().join(&u.file);
+            rerun_if_changed(&path);
+            Some(fs::read(path)?)
+        }
+        None => None,
+    };
+    im.user = user.as_deref();
+
+    let wcfg = WriterConfig::default()
+        .set_header(cfg.output.header)
+        .set_image_hash(cfg.output.image_hash);
+    write_info_to_file(&im, out_dir.as_ref().join(&cfg.output.file), wcfg)?;
+
+    if let Some(ldscript) = &cfg.ldscript {
+        ldscript.generate(out_dir.as_ref())?;
+    }
+
+    Ok(())
+}
+
+/** Do the whole job of a build script that embeds an [`InfoMem`], as
+described by the crate's [`BuildConfig`].
+
+This is a convenience function intended to be used in a [build script](https://doc.rust-lang.org/cargo/reference/build-scripts.html)
+in place of calling [`generate_from_env`], [`write_info_to_file`], and
+[`generate_infomem_ldscript`] by hand:
+
+```ignore
+fn main() {
+    postcard_infomem_host::build_from_config().unwrap();
+}
+```
+
+Like [`generate_from_env`], this function tells Cargo when to rerun the build
+script; see [Rerunning the Build Script](generate_from_env#rerunning-the-build-script).
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include
+those returned by [`read_build_config`] and [`run_build_config`], as well as:
+* [`VarError`](env::VarError): Returned if `CARGO_MANIFEST_DIR` or `OUT_DIR`
+  does not exist.
+*/
+pub fn build_from_config() -> Result<(), Box<dyn Error>> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")?;
+    let out_dir = env::var("OUT_DIR")?;
+
+    let cfg = read_build_config(&manifest_dir)?;
+    run_build_config(&cfg, manifest_dir, out_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use postcard_infomem::{from_bytes_magic, InfoMem};
+
+    #[test]
+    fn config_file() {
+        let dir = TestDir::new("postcard_infomem_config_file");
+        fs::write(
+            dir.join(CONFIG_FILE),
+            indoc! {r#"
+                [env]
+                app-git = false
+                app-date = false
+                rustc-version = false
+                rustc-llvm = false
+                rustc-git = false
+                rustc-host = false
+                rustc-channel = false
+
+                [app]
+                name = "configured"
+                version = "2.0.0-rc.1"
+
+                [rustc]
+                channel = "beta"
+
+                [user]
+                file = "user.bin"
+
+                [output]
+                file = "configured.bin"
+            "#},
+        )
+        .unwrap();
+        fs::write(dir.join("user.bin"), [1, 2, 3]).unwrap();
+
+        let cfg = read_build_config(&dir).unwrap();
+        run_build_config(&cfg, &dir, &dir).unwrap();
+
+        let data = fs::read(dir.join("configured.bin")).unwrap();
+        let im: InfoMem = from_bytes_magic(&data).unwrap();
+        assert_eq!(im.app.name, Some(InfoStr::Borrowed("configured")));
+        assert_eq!(im.app.version.unwrap().to_string(), "2.0.0-rc.1");
+        assert_eq!(im.app.git, None);
+        assert_eq!(im.rustc.channel, Some(Channel::Beta));
+        assert_eq!(im.rustc.host, None);
+        assert_eq!(im.user, Some(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn package_metadata() {
+        let dir = TestDir::new("postcard_infomem_package_metadata");
+        fs::write(
+            dir.join("Cargo.toml"),
+            indoc! {r#"
+                [package]
+                name = "metadata"
+                version = "0.1.0"
+
+                [package.metadata.postcard-infomem.env]
+                app-name = false
+                app-git = false
+
+                [package.metadata.postcard-infomem.output]
+                header = false
+            "#},
+        )
+        .unwrap();
+
+        let cfg = read_build_config(&dir).unwrap();
+        assert!(!cfg.env.app_name);
+        assert!(cfg.env.app_version);
+        assert!(!cfg.output.header);
+        assert!(cfg.user.is_none());
+
+        let cfg = read_build_config(TestDir::new("postcard_infomem_no_config")).unwrap();
+        assert!(cfg.env.app_name);
+        assert!(cfg.output.header);
+    }
+
+    #[test]
+    fn bad_config() {
+        let dir = TestDir::new("postcard_infomem_bad_config");
+        fs::write(dir.join(CONFIG_FILE), "[output]\nfiel = \"typo.bin\"\n").unwrap();
+        assert!(read_build_config(&dir).is_err());
+
+        fs::write(dir.join(CONFIG_FILE), "[app]\nnmae = \"typo\"\n").unwrap();
+        assert!(read_build_config(&dir).is_err());
+
+        fs::write(dir.join(CONFIG_FILE), "[rustc]\nchanel = \"beta\"\n").unwrap();
+        assert!(read_build_config(&dir).is_err());
+
+        fs::write(dir.join(CONFIG_FILE), "[app]\nbuild_date = \"yesterday\"\n").unwrap();
+        assert!(read_build_config(&dir).is_err());
+
+        fs::write(
+            dir.join(CONFIG_FILE),
+            "[ldscript]\ntype = \"hosted\"\nregion = \"FLASH\"\n",
+        )
+        .unwrap();
+        let cfg = read_build_config(&dir).unwrap();
+        assert!(cfg.ldscript.unwrap().generate(&dir).is_err());
+    }
+}
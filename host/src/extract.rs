@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use object::{BinaryFormat, Object, ObjectSection, Section};
+use postcard_infomem::{iter_from_bytes_magic, InfoMem};
+
+/** Find a section by name. Mach-O sections may be qualified by their segment,
+e.g. `__DATA,__pim`. */
+pub(crate) fn find_section<'data, 'file>(
+    file: &'file object::File<'data>,
+    name: &str,
+) -> Option<Section<'data, 'file>> {
+    match name.split_once(',') {
+        Some((segment, sect)) if file.format() == BinaryFormat::MachO => file
+            .sections()
+            .find(|s| s.segment_name().ok().flatten() == Some(segment) && s.name() == Ok(sect)),
+        _ => file.section_by_name(name),
+    }
+}
+
+/** Return the name of the section that [`include_postcard_infomem`](../postcard-infomem-device/macro.include_postcard_infomem.html)
+uses by default for binaries in `format`. */
+pub(crate) fn default_section_name(format: BinaryFormat) -> &'static str {
+    match format {
+        BinaryFormat::Coff | BinaryFormat::Pe => ".pim",
+        BinaryFormat::MachO => "__DATA,__pim",
+        _ => ".postcard_infomem",
+    }
+}
+
+/** Read the contents of a section from an object file or linked binary.
+
+This is intended to be used to extract the section(s) that
+[`include_postcard_infomem`](../postcard-infomem-device/macro.include_postcard_infomem.html)
+places serialized [`InfoMem`]s into (`.postcard_infomem` on most ELF targets,
+`.eeprom` on AVR, `.pim` on Windows, `__DATA,__pim` on macOS). Use
+[`scan_infomem`] to deserialize the contents.
+
+ELF, PE/COFF, and Mach-O files are supported. Mach-O sections may be qualified
+by their segment name, as in the `link_section` attribute.
+
+# Arguments
+* `path`: Name of the object file or binary to read.
+* `section`: Name of the section to extract.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`io::Error`](std::io::Error): Returned if reading the file fails.
+* [`object::Error`]: Returned if parsing the file or section fails.
+
+An error is also returned if the section does not exist.
+*/
+pub fn read_infomem_section<P>(path: P, section: &str) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let data = fs::read(path)?;
+    let file = object::File::parse(&*data)?;
+    let sect =
+        find_section(&file, section).ok_or_else(|| format!("section {} not found", section))?;
+
+    Ok(sect.data()?.to_vec())
+}
+
+/** Read the contents of the default [`InfoMem`] section from an object file
+or linked binary.
+
+This is like [`read_infomem_section`], except that the section name is chosen
+based on the format of the file, matching the defaults of
+[`include_postcard_infomem`](../postcard-infomem-device/macro.include_postcard_infomem.html)
+on hosted targets: `.postcard_infomem` for ELF, `.pim` for PE/COFF, and
+`__DATA,__pim` for Mach-O.
+
+# Errors
+See [`read_infomem_section`].
+*/
+pub fn read_default_infomem_section<P>(path: P) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let data = fs::read(path)?;
+    let file = object::File::parse(&*data)?;
+    let section = default_section_name(file.format());
+    let sect =
+        find_section(&file, section).ok_or_else(|| format!("section {} not found", section))?;
+
+    Ok(sect.data()?.to_vec())
+}
+
+/** Deserialize every [`InfoMem`] `struct` found within a "bag of bytes".
+
+A binary may contain more than one [`InfoMem`], e.g. one each for a bootloader,
+an application, and libraries. These are placed next to each other in the
+same section, possibly with padding in between. Each [`InfoMem`] is found by
+its magic header; see [`iter_from_bytes_magic`] for details. */
+pub fn scan_infomem(data: &[u8]) -> Vec<InfoMem<'_>> {
+    iter_from_bytes_magic(data).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postcard_infomem::to_stdvec_magic;
+
+    #[test]
+    fn scan_multiple() {
+        let mut boot: InfoMem = InfoMem::default();
+        boot.app.name = Some("boot".into());
+        let mut app: InfoMem = InfoMem::default();
+        app.app.name = Some("app".into());
+
+        let mut data = to_stdvec_magic(&boot).unwrap();
+        data.extend([0xff; 16]);
+        data.extend(to_stdvec_magic(&app).unwrap());
+
+        assert_eq!(scan_infomem(&data), vec![boot, app]);
+    }
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "fixtures", name]
+            .iter()
+            .collect()
+    }
+
+    fn assert_fixture_infomem(sect: &[u8]) {
+        let ims = scan_infomem(sect);
+
+        assert_eq!(ims.len(), 1);
+        assert_eq!(ims[0].app.name, Some("fixture".into()));
+    }
+
+    // Assembled from fixtures/x86_64-windows.s with llvm-mc, and linked with
+    // GNU ld -m i386pep.
+    #[test]
+    fn read_pe() {
+        let exe = fixture("x86_64-windows.exe");
+
+        assert_fixture_infomem(&read_infomem_section(&exe, ".pim").unwrap());
+        assert_fixture_infomem(&read_default_infomem_section(&exe).unwrap());
+    }
+
+    // Assembled from fixtures/x86_64-macos.s with llvm-mc.
+    #[test]
+    fn read_macho() {
+        let obj = fixture("x86_64-macos.o");
+
+        assert_fixture_infomem(&read_infomem_section(&obj, "__DATA,__pim").unwrap());
+        assert_fixture_infomem(&read_infomem_section(&obj, "__pim").unwrap());
+        assert_fixture_infomem(&read_default_infomem_section(&obj).unwrap());
+        assert!(read_infomem_section(&obj, "__TEXT,__pim").is_err());
+    }
+
+    #[test]
+    fn read_elf_default() {
+        assert_fixture_infomem(&read_default_infomem_section(fixture("thumbv6m.elf")).unwrap());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[link_section = ".postcard_infomem"]
+    #[used]
+    static TEST_INFOMEM: [u8; 4] = *b"PIM\x80";
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn read_current_exe() {
+        let exe = std::env::current_exe().unwrap();
+        let sect = read_infomem_section(exe, ".postcard_infomem").unwrap();
+
+        assert!(sect.windows(4).any(|w| w == b"PIM\x80"));
+    }
+}
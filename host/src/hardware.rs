@@ -0,0 +1,180 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use postcard_infomem::{to_stdvec_hardware, HardwareAddress, HardwareInfo};
+use time::OffsetDateTime;
+
+/** Arguments to [`generate_hardware_info`].
+
+This is intended to be filled in by a factory tool at production time, e.g.
+from command-line arguments or a manufacturing database. By default, no fields
+are populated. */
+#[derive(Default)]
+pub struct HardwareConfig<'a> {
+    board: Option<&'a str>,
+    revision: Option<&'a str>,
+    serial: Option<&'a str>,
+    address: Option<&'a str>,
+    date: bool,
+}
+
+impl<'a> HardwareConfig<'a> {
+    /// Set [`HardwareInfo::board`].
+    pub fn set_board(mut self, board: &'a str) -> Self {
+        self.board = Some(board);
+        self
+    }
+
+    /// Set [`HardwareInfo::revision`].
+    pub fn set_revision(mut self, revision: &'a str) -> Self {
+        self.revision = Some(revision);
+        self
+    }
+
+    /// Set [`HardwareInfo::serial`].
+    pub fn set_serial(mut self, serial: &'a str) -> Self {
+        self.serial = Some(serial);
+        self
+    }
+
+    /** Set [`HardwareInfo::address`] from a string of 6 (MAC) or 8 (EUI-64)
+    hexadecimal octets, separated by `:` or `-`, e.g. `02:00:00:12:34:56`. */
+    pub fn set_address(mut self, address: &'a str) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /** If `true`, set [`HardwareInfo::manufacture_date`] to the current
+    _local_ time. */
+    pub fn set_manufacture_date(mut self, op: bool) -> Self {
+        self.date = op;
+        self
+    }
+}
+
+/// Parse a MAC or EUI-64 address.
+fn parse_address(s: &str) -> Result<HardwareAddress, Box<dyn Error>> {
+    let octets = s
+        .split([':', '-'])
+        .map(|o| match o.len() {
+            2 => u8::from_str_radix(o, 16).ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| format!("invalid hardware address {}", s))?;
+
+    match octets.len() {
+        6 => Ok(HardwareAddress::Mac(octets.try_into().unwrap())),
+        8 => Ok(HardwareAddress::Eui64(octets.try_into().unwrap())),
+        n => Err(format!("hardware address {} has {} octets, expected 6 or 8", s, n).into()),
+    }
+}
+
+/** Populate a [`HardwareInfo`] struct for one board.
+
+Unlike [`generate_from_env`](crate::generate_from_env), this function is
+intended to run at production time, rather than in a build script. The result
+can be written out with [`write_hardware_info_to_file`] and programmed into the
+board, where the firmware reads it with
+[`from_bytes_hardware`](postcard_infomem::from_bytes_hardware).
+
+# Arguments
+* `cfg`: Values of the fields of [`HardwareInfo`] to set.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`IndeterminateOffset`](time::error::IndeterminateOffset): Returned if getting the local time fails.
+
+An error is also returned if the hardware address cannot be parsed.
+*/
+pub fn generate_hardware_info<'a>(
+    cfg: HardwareConfig<'a>,
+) -> Result<HardwareInfo<'a>, Box<dyn Error>> {
+    let mut hw = HardwareInfo {
+        board: cfg.board.map(Into::into),
+        revision: cfg.revision.map(Into::into),
+        serial: cfg.serial.map(Into::into),
+        ..Default::default()
+    };
+
+    if let Some(address) = cfg.address {
+        hw.address = Some(parse_address(address)?);
+    }
+
+    if cfg.date {
+        hw.manufacture_date = Some(OffsetDateTime::now_local()?);
+    }
+
+    Ok(hw)
+}
+
+/** Write out a serialized [`HardwareInfo`] structure, preceded by its magic
+header, to file.
+
+The file is intended to be programmed into the board as-is.
+
+# Arguments
+* `hw`: [`HardwareInfo`] `struct` to write out.
+* `path`: Name of file to write to.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`io::Error`](std::io::Error): Returned if creating or writing the file fails.
+* [`postcard::Error`]: Returned if serializing `hw` fails.
+*/
+pub fn write_hardware_info_to_file<P>(hw: &HardwareInfo, path: P) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let buf = to_stdvec_hardware(hw)?;
+
+    let mut fp = File::create(path)?;
+    fp.write_all(&buf)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestDir;
+    use std::fs;
+
+    use postcard_infomem::from_bytes_hardware;
+
+    #[test]
+    fn generate_and_write() {
+        let dir = TestDir::new("postcard_infomem_generate_and_write");
+        let path = dir.join("hardware.bin");
+        let cfg = HardwareConfig::default()
+            .set_board("pim-devkit")
+            .set_serial("000123")
+            .set_address("02-00-00-12-34-56-78-9a");
+
+        let hw = generate_hardware_info(cfg).unwrap();
+        write_hardware_info_to_file(&hw, &path).unwrap();
+
+        let data = fs::read(&path).unwrap();
+        let de = from_bytes_hardware(&data).unwrap();
+        assert_eq!(de, hw);
+        assert_eq!(de.revision, None);
+        assert_eq!(
+            de.address,
+            Some(HardwareAddress::Eui64([
+                2, 0, 0, 0x12, 0x34, 0x56, 0x78, 0x9a
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_mac() {
+        assert_eq!(
+            parse_address("02:00:00:12:34:ab").unwrap(),
+            HardwareAddress::Mac([2, 0, 0, 0x12, 0x34, 0xab])
+        );
+        assert!(parse_address("02:00:00:12:34").is_err());
+        assert!(parse_address("02:00:00:12:34:zz").is_err());
+        assert!(parse_address("0200:00:12:34:56").is_err());
+    }
+}
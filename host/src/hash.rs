@@ -0,0 +1,274 @@
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use object::elf::PT_LOAD;
+use object::read::elf::{ElfFile, FileHeader, ProgramHeader};
+use object::{elf, FileKind, ObjectSection};
+use postcard_infomem::{find_image_hash, image_hash, HASH_LEN};
+
+use crate::extract::{default_section_name, find_section};
+use crate::verify::VerifyError;
+
+/** Post-link image hash configuration for [`patch_image_hash`].
+
+By default, the image hash block is looked up in the link section that
+`include_postcard_infomem` uses by default for the format of the binary
+(`.postcard_infomem` for ELF), and all loadable segments of the binary are
+hashed. */
+#[derive(Default)]
+pub struct HashConfig<'a> {
+    section: Option<&'a str>,
+    region: Option<(u64, u64)>,
+}
+
+impl<'a> HashConfig<'a> {
+    /** Set the name of the link section containing the image hash block,
+    which must match the `section` option passed to `include_postcard_infomem`. */
+    pub fn set_section_name(mut self, name: &'a str) -> Self {
+        self.section = Some(name);
+        self
+    }
+
+    /** Only hash loadable segments whose load address lies within `length`
+    bytes starting at `origin`.
+
+    This is usually the flash region of a microcontroller, so that segments
+    copied to or zeroed in RAM at startup are not hashed twice. */
+    pub fn set_region(mut self, origin: u64, length: u64) -> Self {
+        self.region = Some((origin, length));
+        self
+    }
+}
+
+/** Return the range of `data` covering `size` bytes at `offset`, or `None` if
+it does not lie entirely within `data`. */
+fn file_range(data: &[u8], offset: u64, size: u64) -> Option<Range<usize>> {
+    let start = usize::try_from(offset).ok()?;
+    let end = start.checked_add(usize::try_from(size).ok()?)?;
+
+    data.get(start..end).map(|_| start..end)
+}
+
+/// Load (physical) address and file range of a loadable segment.
+type Segment = (u64, Range<usize>);
+
+/** Return the load (physical) addresses and file ranges of the non-empty
+loadable segments of an ELF file, in order of load address. */
+fn load_segments<Elf>(
+    data: &[u8],
+    region: Option<(u64, u64)>,
+) -> Result<Vec<Segment>, Box<dyn Error>>
+where
+    Elf: FileHeader,
+{
+    let file = ElfFile::<Elf>::parse(data)?;
+    let endian = file.endian();
+
+    let mut segs = Vec::new();
+    for ph in file.raw_segments() {
+        let size: u64 = ph.p_filesz(endian).into();
+        if ph.p_type(endian) != PT_LOAD || size == 0 {
+            continue;
+        }
+
+        let addr: u64 = ph.p_paddr(endian).into();
+        let in_region = match region {
+            Some((origin, length)) => match (addr.checked_add(size), origin.checked_add(length)) {
+                (Some(end), Some(region_end)) => addr >= origin && end <= region_end,
+                _ => false,
+            },
+            None => true,
+        };
+
+        if in_region {
+            let range = file_range(data, ph.p_offset(endian).into(), size)
+                .ok_or("loadable segment lies outside the file")?;
+            segs.push((addr, range));
+        }
+    }
+
+    segs.sort_by_key(|(addr, _)| *addr);
+    Ok(segs)
+}
+
+/** Compute the SHA-256 image hash of a linked ELF binary, and patch it into
+the image hash block following the serialized [`InfoMem`](postcard_infomem::InfoMem)
+at the start of the link section.
+
+The image hash block must have been reserved before linking, by enabling
+[`WriterConfig::set_image_hash`](crate::WriterConfig::set_image_hash). The
+digest covers the file contents of the selected `PT_LOAD` segments in order of
+load address, with the digest itself (at its load address) replaced by zeros;
+see [`postcard_infomem`'s documentation](postcard_infomem::find_image_hash)
+for details. The application can check the digest at runtime with
+[`verify_image_hash`](postcard_infomem::verify_image_hash).
+
+The file is modified in place; the digest is also returned. This function must
+run _after_ any other post-link step that modifies loadable segments, e.g.
+signing.
+
+# Arguments
+* `path`: Name of the linked binary to patch.
+* `cfg`: Image hash configuration.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`VerifyError::MissingSection`]: Returned if the link section does not exist.
+* [`VerifyError::EmptySection`]: Returned if the link section has no contents
+  in the file.
+* [`io::Error`](std::io::Error): Returned if reading or writing the file fails.
+* [`object::Error`]: Returned if parsing the file or section fails.
+* [`postcard::Error`]: Returned if the link section does not start with a
+  serialized [`InfoMem`](postcard_infomem::InfoMem).
+
+An error is also returned if the file is not an ELF file, if the link section
+has no image hash block, if no segments were selected for hashing, or if the
+link section or a segment lies outside the file.
+*/
+pub fn patch_image_hash<P>(path: P, cfg: HashConfig) -> Result<[u8; HASH_LEN], Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let mut data = fs::read(&path)?;
+
+    let segs = match FileKind::parse(&*data)? {
+        FileKind::Elf32 => {
+            load_segments::<elf::FileHeader32<object::Endianness>>(&data, cfg.region)?
+        }
+        FileKind::Elf64 => {
+            load_segments::<elf::FileHeader64<object::Endianness>>(&data, cfg.region)?
+        }
+        _ => return Err("image hashing requires an ELF file".into()),
+    };
+
+    if segs.is_empty() {
+        return Err("no loadable segments to hash".into());
+    }
+
+    let file = object::File::parse(&*data)?;
+    let section = cfg
+        .section
+        .unwrap_or_else(|| default_section_name(file.format()));
+    let sect =
+        find_section(&file, section).ok_or_else(|| VerifyError::MissingSection(section.into()))?;
+    let sect_addr = sect.address();
+    let (sect_offset, sect_size) = sect
+        .file_range()
+        .ok_or_else(|| VerifyError::EmptySection(section.into()))?;
+
+    let sect = file_range(&data, sect_offset, sect_size)
+        .ok_or_else(|| format!("section {} lies outside the file", section))?;
+    let (field_offset, _) = find_image_hash(&data[sect.clone()])?
+        .ok_or_else(|| format!("section {} has no image hash block", section))?;
+    let field = sect.start + field_offset;
+
+    // Use the load address of the digest if a hashed segment contains it, so
+    // that it matches the address seen by the device. Otherwise, the digest is
+    // not hashed, and any address outside of the segments will do.
+    let field_addr = segs
+        .iter()
+        .find(|(_, range)| range.contains(&field))
+        .map(|(addr, range)| addr + (field - range.start) as u64)
+        .unwrap_or_else(|| sect_addr.saturating_add(field_offset as u64));
+
+    let digest = image_hash(
+        segs.iter()
+            .map(|(addr, range)| (*addr, &data[range.clone()])),
+        field_addr,
+    );
+
+    data[field..field + HASH_LEN].copy_from_slice(&digest);
+    fs::write(path, data)?;
+
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestDir;
+
+    use postcard_infomem::{verify_image_hash, HashError};
+
+    // Assembled from fixtures/thumbv6m-hash.s with llvm-mc, and linked from
+    // fixtures/thumbv6m-hash.x with GNU ld -n:
+    //   llvm-mc -triple=thumbv6m-none-eabi -filetype=obj -o thumbv6m-hash.o thumbv6m-hash.s
+    //   arm-none-eabi-ld -n -T thumbv6m-hash.x -o thumbv6m-hash.elf thumbv6m-hash.o
+    // .text contains 0x40 bytes of code, and .postcard_infomem contains one
+    // InfoMem with app.name = "fixture", followed by an unpatched image hash
+    // block.
+    const THUMBV6M_HASH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/thumbv6m-hash.elf");
+
+    fn copy_fixture(dir: &TestDir) -> std::path::PathBuf {
+        let path = dir.join("image.elf");
+        fs::copy(THUMBV6M_HASH, &path).unwrap();
+        path
+    }
+
+    #[test]
+    fn patch_and_verify() {
+        let dir = TestDir::new("postcard_infomem_patch_and_verify");
+        let path = copy_fixture(&dir);
+        let cfg = HashConfig::default().set_region(0x10000000, 0x2000);
+        let digest = patch_image_hash(&path, cfg).unwrap();
+
+        // Reconstruct the loaded image as the device would see it.
+        let data = fs::read(&path).unwrap();
+        let file = object::File::parse(&*data).unwrap();
+        let text = find_section(&file, ".text").unwrap();
+        let info = find_section(&file, ".postcard_infomem").unwrap();
+        let (text_addr, text) = (text.address(), text.data().unwrap());
+        let (info_addr, info) = (info.address(), info.data().unwrap());
+
+        let (offset, hash) = find_image_hash(info).unwrap().unwrap();
+        assert_eq!(hash, &digest);
+        assert_ne!(hash, &[0; HASH_LEN]);
+        assert_eq!(info[offset - 4..offset], *b"PIH\x80");
+        assert_eq!(
+            verify_image_hash(info, info_addr, [(text_addr, text), (info_addr, info)]),
+            Ok(())
+        );
+        assert_eq!(
+            verify_image_hash(info, info_addr, [(info_addr, info), (text_addr, text)]),
+            Err(HashError::Mismatch)
+        );
+
+        // Patching again must be idempotent.
+        let cfg = HashConfig::default().set_region(0x10000000, 0x2000);
+        assert_eq!(patch_image_hash(&path, cfg).unwrap(), digest);
+    }
+
+    #[test]
+    fn patch_no_hash_block() {
+        let dir = TestDir::new("postcard_infomem_patch_no_hash_block");
+        let path = dir.join("image.elf");
+        fs::copy(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/thumbv6m.elf"),
+            &path,
+        )
+        .unwrap();
+
+        assert!(patch_image_hash(&path, HashConfig::default()).is_err());
+    }
+
+    #[test]
+    fn patch_no_segments() {
+        let dir = TestDir::new("postcard_infomem_patch_no_segments");
+        let path = copy_fixture(&dir);
+        let cfg = HashConfig::default().set_region(0x20000000, 0x1000);
+
+        assert!(patch_image_hash(&path, cfg).is_err());
+    }
+
+    #[test]
+    fn patch_truncated() {
+        let dir = TestDir::new("postcard_infomem_patch_truncated");
+        let path = dir.join("image.elf");
+        let data = fs::read(THUMBV6M_HASH).unwrap();
+        fs::write(&path, &data[..0x80]).unwrap();
+
+        assert!(patch_image_hash(&path, HashConfig::default()).is_err());
+    }
+}
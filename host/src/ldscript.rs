@@ -1,6 +1,7 @@
 use std::collections::HashMap;
-use std::env;
+use std::env::{self, VarError};
 use std::error::Error;
+use std::fmt;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
@@ -13,134 +14,433 @@ static INFOMEM_LINKER_SCRIPT_TEMPLATE: &str = r#"
 
 SECTIONS {
     { alignment }
-    .postcard_infomem : {
-        _spostcard_infomem = .;
-        KEEP(*(.postcard_infomem))
-        _epostcard_infomem = .;
+    {section} {address}:{section_align} {
+        { fill }
+        {start_symbol} = .;
+        KEEP(*({section}))
+        {end_symbol} = .;
     } {memory_region}
 } {insert_before_after}
 "#;
 
+/// Default name of the link section that holds serialized `InfoMem`s.
+const DEFAULT_SECTION: &str = ".postcard_infomem";
+
+/** Default name of the link section on Windows. Section names in PE images are
+limited to 8 characters. */
+const DEFAULT_PE_SECTION: &str = ".pim";
+
+/** Linker fragment configuration for the current target.
+
+An [`LdConfig`] is created by converting one of [`HostedConfig`],
+[`BareSectionConfig`], or [`BareAppendConfig`] with [`TryFrom`]/[`TryInto`],
+which checks the configuration against the target that the build script is
+running for. Functions which accept any of these configuration `struct`s, like
+[`generate_infomem_ldscript`], are generic over `TryInto<LdConfig>`. */
+#[derive(Debug)]
 pub struct LdConfig<'a> {
+    section: &'a str,
     region: Option<&'a str>,
+    address: Option<Address>,
     insert: InsertType<'a>,
     alignment: Option<&'a str>,
+    section_align: Option<u64>,
+    fill: Option<u8>,
+}
+
+/** Error returned when a linker fragment configuration cannot be converted into
+an [`LdConfig`] for the current target.
+
+[`generate_infomem_ldscript`] returns this error (casted to [`Box<dyn Error>`])
+before writing any files, so build scripts can downcast it and fall back
+gracefully, e.g. by skipping linker fragment generation on unsupported targets. */
+#[derive(Debug)]
+pub enum LdConfigError {
+    /// The configuration does not support the current target.
+    UnsupportedTarget {
+        /// Name of the configuration `struct`.
+        config: &'static str,
+        /// Value of `CARGO_CFG_TARGET_OS`.
+        os: String,
+        /// Value of `CARGO_CFG_TARGET_ENV`.
+        env: String,
+    },
+    /// An environment variable describing the current target could not be read.
+    /// This usually means the caller is not a build script.
+    MissingEnvVar(&'static str, VarError),
+    /// The alignment of the link section is not a power of two.
+    InvalidAlignment(u64),
+}
+
+impl fmt::Display for LdConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LdConfigError::UnsupportedTarget { config, os, env } => write!(
+                f,
+                "{} is not compatible with target_os = \"{}\", target_env = \"{}\"",
+                config, os, env
+            ),
+            LdConfigError::MissingEnvVar(var, e) => {
+                write!(f, "could not read environment variable {}: {}", var, e)
+            }
+            LdConfigError::InvalidAlignment(align) => {
+                write!(f, "alignment {} is not a power of two", align)
+            }
+        }
+    }
 }
 
+impl Error for LdConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LdConfigError::UnsupportedTarget { .. } | LdConfigError::InvalidAlignment(_) => None,
+            LdConfigError::MissingEnvVar(_, e) => Some(e),
+        }
+    }
+}
+
+/// Target that a linker fragment is generated for, as seen by a build script.
+struct Target {
+    os: String,
+    env: String,
+}
+
+impl Target {
+    fn new(os: &str, env: &str) -> Self {
+        Self {
+            os: os.into(),
+            env: env.into(),
+        }
+    }
+
+    fn from_env() -> Result<Self, LdConfigError> {
+        let var = |name| env::var(name).map_err(|e| LdConfigError::MissingEnvVar(name, e));
+
+        Ok(Self::new(
+            &var("CARGO_CFG_TARGET_OS")?,
+            &var("CARGO_CFG_TARGET_ENV")?,
+        ))
+    }
+
+    fn unsupported(&self, config: &'static str) -> LdConfigError {
+        LdConfigError::UnsupportedTarget {
+            config,
+            os: self.os.clone(),
+            env: self.env.clone(),
+        }
+    }
+}
+
+/// Fixed placement of the link section in memory.
+#[derive(Clone, Copy, Debug)]
+enum Address {
+    /// Absolute address.
+    Absolute(u64),
+    /// Offset from the start of the memory region the section is placed in.
+    RegionOffset(u64),
+}
+
+#[derive(Clone, Debug)]
 enum InsertType<'a> {
     None,
-    #[allow(dead_code)]
     Before(&'a str),
     After(&'a str),
 }
 
-pub struct BareSectionConfig<'a> {
+/// Settings shared by [`BareSectionConfig`] and [`BareAppendConfig`].
+#[derive(Clone)]
+pub(crate) struct BareConfig<'a> {
+    section: &'a str,
     region: &'a str,
+    address: Option<Address>,
+    insert: InsertType<'a>,
+    align: Option<u64>,
+    fill: Option<u8>,
 }
 
-impl<'a> BareSectionConfig<'a> {
-    pub fn set_memory_region(mut self, reg: &'a str) -> Self {
+impl<'a> BareConfig<'a> {
+    fn new(region: &'a str, insert: InsertType<'a>) -> Self {
+        Self {
+            section: DEFAULT_SECTION,
+            region,
+            address: None,
+            insert,
+            align: None,
+            fill: None,
+        }
+    }
+
+    pub(crate) fn set_memory_region(mut self, reg: &'a str) -> Self {
         self.region = reg;
         self
     }
+
+    pub(crate) fn set_address(mut self, addr: u64) -> Self {
+        self.address = Some(Address::Absolute(addr));
+        self
+    }
+
+    pub(crate) fn set_region_offset(mut self, offset: u64) -> Self {
+        self.address = Some(Address::RegionOffset(offset));
+        self
+    }
+
+    pub(crate) fn set_section_name(mut self, name: &'a str) -> Self {
+        self.section = name;
+        self
+    }
+
+    pub(crate) fn set_insert_before(mut self, out_section: &'a str) -> Self {
+        self.insert = InsertType::Before(out_section);
+        self
+    }
+
+    pub(crate) fn set_insert_after(mut self, out_section: &'a str) -> Self {
+        self.insert = InsertType::After(out_section);
+        self
+    }
+
+    pub(crate) fn set_alignment(mut self, align: u64) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    pub(crate) fn set_fill(mut self, fill: u8) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    fn into_ldconfig(self) -> Result<LdConfig<'a>, LdConfigError> {
+        if let Some(align) = self.align.filter(|a| !a.is_power_of_two()) {
+            return Err(LdConfigError::InvalidAlignment(align));
+        }
+
+        Ok(LdConfig {
+            section: self.section,
+            region: Some(self.region),
+            address: self.address,
+            insert: self.insert,
+            alignment: None,
+            section_align: self.align,
+            fill: self.fill,
+        })
+    }
 }
 
+/// Implement the public setters of a `struct` wrapping a [`BareConfig`].
+macro_rules! bare_setters {
+    ($config:ident) => {
+        impl<'a> $config<'a> {
+            /// Place the link section in the memory region `reg`.
+            pub fn set_memory_region(self, reg: &'a str) -> Self {
+                Self(self.0.set_memory_region(reg))
+            }
+
+            /** Place the link section at the absolute address `addr`, e.g. so that a
+            bootloader can find it. The address must lie within the memory region.
+            See also [`write_address_const`]. */
+            pub fn set_address(self, addr: u64) -> Self {
+                Self(self.0.set_address(addr))
+            }
+
+            /** Place the link section `offset` bytes after the start of the memory
+            region, e.g. so that a bootloader can find it. See also
+            [`write_address_const`]. */
+            pub fn set_region_offset(self, offset: u64) -> Self {
+                Self(self.0.set_region_offset(offset))
+            }
+
+            /** Set the name of the link section, which must match the `section`
+            option passed to `include_postcard_infomem`. The symbols delimiting the
+            section are named after it, e.g. `.fw_info` is delimited by `_sfw_info`
+            and `_efw_info`. */
+            pub fn set_section_name(self, name: &'a str) -> Self {
+                Self(self.0.set_section_name(name))
+            }
+
+            /** Insert the link section into the default linker script before the
+            output section `out_section`, using `INSERT BEFORE`. */
+            pub fn set_insert_before(self, out_section: &'a str) -> Self {
+                Self(self.0.set_insert_before(out_section))
+            }
+
+            /** Insert the link section into the default linker script after the
+            output section `out_section`, using `INSERT AFTER`. */
+            pub fn set_insert_after(self, out_section: &'a str) -> Self {
+                Self(self.0.set_insert_after(out_section))
+            }
+
+            /** Align the start of the link section to `align` bytes, which must be a
+            power of two. Otherwise, converting the configuration into an
+            [`LdConfig`] fails with [`LdConfigError::InvalidAlignment`]. */
+            pub fn set_alignment(self, align: u64) -> Self {
+                Self(self.0.set_alignment(align))
+            }
+
+            /** Fill any gaps within the link section, e.g. between multiple
+            [`InfoMem`](postcard_infomem::InfoMem)s, with `fill`. This should match the
+            `fill` option passed to `include_postcard_infomem`. */
+            pub fn set_fill(self, fill: u8) -> Self {
+                Self(self.0.set_fill(fill))
+            }
+        }
+    };
+}
+
+/** Linker fragment configuration for bare-metal applications, which places the
+link section in its own memory region (`INFOMEM` by default).
+
+Supported targets are `target_os = "none"` and `target_os = "unknown"`. */
+#[derive(Clone)]
+pub struct BareSectionConfig<'a>(pub(crate) BareConfig<'a>);
+
+bare_setters!(BareSectionConfig);
+
 impl<'a> Default for BareSectionConfig<'a> {
     fn default() -> Self {
-        Self { region: "INFOMEM" }
+        Self(BareConfig::new("INFOMEM", InsertType::None))
     }
 }
 
-impl<'a> From<BareSectionConfig<'a>> for LdConfig<'a> {
-    fn from(value: BareSectionConfig<'a>) -> Self {
-        if cfg!(test)
-            || env::var("CARGO_CFG_TARGET_OS").unwrap() == "none"
-            || env::var("CARGO_CFG_TARGET_OS").unwrap() == "unknown"
-        {
-            LdConfig {
-                region: Some(value.region),
-                insert: InsertType::None,
-                alignment: None,
-            }
-        } else {
-            panic!("BareAppendConfig is only compatible with target_os = \"none\", current target_os = \"{}\"", env::var("CARGO_CFG_TARGET_OS").unwrap());
+impl<'a> BareSectionConfig<'a> {
+    fn into_ldconfig(self, target: &Target) -> Result<LdConfig<'a>, LdConfigError> {
+        match &*target.os {
+            "none" | "unknown" => self.0.into_ldconfig(),
+            _ => Err(target.unsupported("BareSectionConfig")),
         }
     }
 }
 
-pub struct BareAppendConfig<'a> {
-    out_section: &'a str,
-    region: &'a str,
-}
+impl<'a> TryFrom<BareSectionConfig<'a>> for LdConfig<'a> {
+    type Error = LdConfigError;
 
-impl<'a> BareAppendConfig<'a> {
-    pub fn set_memory_region(mut self, reg: &'a str) -> Self {
-        self.region = reg;
-        self
+    fn try_from(value: BareSectionConfig<'a>) -> Result<Self, Self::Error> {
+        value.into_ldconfig(&Target::from_env()?)
     }
 }
 
+/** Linker fragment configuration for bare-metal applications, which appends
+the link section to an existing memory region (`FLASH` by default), after
+`.rodata` unless placed otherwise.
+
+The supported target is `target_os = "none"`. */
+#[derive(Clone)]
+pub struct BareAppendConfig<'a>(pub(crate) BareConfig<'a>);
+
+bare_setters!(BareAppendConfig);
+
 impl<'a> Default for BareAppendConfig<'a> {
     fn default() -> Self {
-        Self {
-            out_section: ".rodata",
-            region: "FLASH",
-        }
+        Self(BareConfig::new("FLASH", InsertType::After(".rodata")))
     }
 }
 
-impl<'a> From<BareAppendConfig<'a>> for LdConfig<'a> {
-    fn from(value: BareAppendConfig<'a>) -> Self {
-        if cfg!(test) || env::var("CARGO_CFG_TARGET_OS").unwrap() == "none" {
-            LdConfig {
-                region: Some(value.region),
-                insert: InsertType::After(value.out_section),
-                alignment: None,
-            }
-        } else {
-            panic!("BareAppendConfig is only compatible with target_os = \"none\"");
+impl<'a> BareAppendConfig<'a> {
+    fn into_ldconfig(self, target: &Target) -> Result<LdConfig<'a>, LdConfigError> {
+        match &*target.os {
+            "none" => self.0.into_ldconfig(),
+            _ => Err(target.unsupported("BareAppendConfig")),
         }
     }
 }
 
-pub struct HostedConfig {}
+impl<'a> TryFrom<BareAppendConfig<'a>> for LdConfig<'a> {
+    type Error = LdConfigError;
 
-impl<'a> Default for HostedConfig {
-    fn default() -> Self {
-        Self {}
+    fn try_from(value: BareAppendConfig<'a>) -> Result<Self, Self::Error> {
+        value.into_ldconfig(&Target::from_env()?)
     }
 }
 
-impl<'a> From<HostedConfig> for LdConfig<'a> {
-    fn from(_value: HostedConfig) -> Self {
-        if cfg!(test)
-            || (env::var("CARGO_CFG_TARGET_OS").unwrap() == "windows"
-                && env::var("CARGO_CFG_TARGET_ENV").unwrap() == "gnu")
-        {
-            LdConfig {
+/** Linker fragment configuration for applications running under an OS.
+
+Supported targets are:
+
+* `target_os = "linux"`: The link section is inserted after `.rodata`, which
+  works with both GNU `ld` and `lld`. Since the fragment uses `INSERT`, the
+  linker's default script remains in effect.
+* `target_os = "windows"`, `target_env = "gnu"`: The link section is inserted
+  after `.text`. The link section is named `.pim` by default, matching
+  `include_postcard_infomem`.
+
+In both cases, `KEEP` prevents the linker from garbage-collecting the section,
+and the `_spostcard_infomem`/`_epostcard_infomem` symbols (`_spim`/`_epim` on
+Windows) are defined around it.
+*/
+#[derive(Clone, Default)]
+pub struct HostedConfig<'a> {
+    section: Option<&'a str>,
+}
+
+impl<'a> HostedConfig<'a> {
+    /** Set the name of the link section, which must match the `section`
+    option passed to `include_postcard_infomem`. The symbols delimiting the
+    section are named after it, e.g. `.fw_info` is delimited by `_sfw_info`
+    and `_efw_info`. */
+    pub fn set_section_name(mut self, name: &'a str) -> Self {
+        self.section = Some(name);
+        self
+    }
+
+    fn into_ldconfig(self, target: &Target) -> Result<LdConfig<'a>, LdConfigError> {
+        match (&*target.os, &*target.env) {
+            ("windows", "gnu") => Ok(LdConfig {
+                section: self.section.unwrap_or(DEFAULT_PE_SECTION),
                 region: None,
+                address: None,
                 insert: InsertType::After(".text"),
                 alignment: Some("__section_alignment__"),
-            }
-        // This will never be supported...
-        } else if env::var("CARGO_CFG_TARGET_OS").unwrap() == "none" {
-            panic!("HostedConfig is not compatible with target_os = \"none\"");
-        // but some OSes that match this might be.
-        } else {
-            panic!(
-                "HostedConfig is not compatible with target_os = {}, target_env = {}",
-                env::var("CARGO_CFG_TARGET_OS").unwrap(),
-                env::var("CARGO_CFG_TARGET_ENV").unwrap()
-            );
+                section_align: None,
+                fill: None,
+            }),
+            ("linux", _) => Ok(LdConfig {
+                section: self.section.unwrap_or(DEFAULT_SECTION),
+                region: None,
+                address: None,
+                insert: InsertType::After(".rodata"),
+                alignment: None,
+                section_align: None,
+                fill: None,
+            }),
+            // target_os = "none" will never be supported, but some other OSes
+            // might be.
+            _ => Err(target.unsupported("HostedConfig")),
         }
     }
 }
 
+impl<'a> TryFrom<HostedConfig<'a>> for LdConfig<'a> {
+    type Error = LdConfigError;
+
+    fn try_from(value: HostedConfig<'a>) -> Result<Self, Self::Error> {
+        value.into_ldconfig(&Target::from_env()?)
+    }
+}
+
+/** Write out a linker fragment which keeps the link section containing
+serialized [`InfoMem`](postcard_infomem::InfoMem)s, and tell Cargo to pass it
+to the linker.
+
+This is a convenience function intended to be used in a [build script](https://doc.rust-lang.org/cargo/reference/build-scripts.html).
+
+# Arguments
+* `path`: Name of file to write to.
+* `cfg`: Linker fragment configuration, e.g. [`BareSectionConfig`],
+  [`BareAppendConfig`], or [`HostedConfig`].
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`LdConfigError`]: Returned if `cfg` does not support the current target, if
+  the current target could not be determined, or if `cfg` is invalid.
+* [`io::Error`](std::io::Error): Returned if creating or writing the file fails.
+*/
 pub fn generate_infomem_ldscript<'a, P, L>(path: P, cfg: L) -> Result<(), Box<dyn Error>>
 where
     P: AsRef<Path>,
-    L: Into<LdConfig<'a>>,
+    L: TryInto<LdConfig<'a>, Error = LdConfigError>,
 {
+    let cfg = cfg.try_into()?;
+
     let filename = path
         .as_ref()
         .file_name()
@@ -151,7 +451,7 @@ where
         .parent()
         .ok_or("invalid path for linker script")?
         .to_string_lossy();
-    let script = generate_script(cfg.into())?;
+    let script = generate_script(cfg)?;
     let mut fp = File::create(&path)?;
     fp.write_all(&script.as_bytes())?;
 
@@ -161,6 +461,86 @@ where
     Ok(())
 }
 
+/** Write out a Rust source file defining a `const` with the address of the
+link section.
+
+This is intended to be used when the link section is placed at a fixed address
+with [`BareSectionConfig::set_address`] or similar, so that e.g. a bootloader can
+read the application's [`InfoMem`](postcard_infomem::InfoMem) before jumping to
+it. The address is derived from the same linker fragment configuration passed
+to [`generate_infomem_ldscript`], so the two cannot disagree. The generated
+file can be included into the bootloader with [`include!`]:
+
+```ignore
+let cfg = BareSectionConfig::default().set_region_offset(0x100);
+generate_infomem_ldscript(out.join("info.x"), cfg.clone())?;
+write_address_const(out.join("infomem_addr.rs"), "INFOMEM_ADDR", cfg, Some(0x10001000))?;
+
+// In the bootloader:
+include!(concat!(env!("OUT_DIR"), "/infomem_addr.rs"));
+```
+
+# Arguments
+* `path`: Name of file to write to.
+* `name`: Name of the generated `const`, e.g. `INFOMEM_ADDR`.
+* `cfg`: Linker fragment configuration, e.g. [`BareSectionConfig`] or
+  [`BareAppendConfig`].
+* `region_origin`: Origin of the memory region the link section is placed in.
+  Only required if the link section is placed with `set_region_offset`.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`LdConfigError`]: Returned if `cfg` does not support the current target, if
+  the current target could not be determined, or if `cfg` is invalid.
+* [`io::Error`](std::io::Error): Returned if creating or writing the file fails.
+
+An error is also returned if `cfg` does not place the link section at a fixed
+address, or if it is placed with `set_region_offset` and `region_origin` is
+[`None`] (or the address overflows).
+*/
+pub fn write_address_const<'a, P, L>(
+    path: P,
+    name: &str,
+    cfg: L,
+    region_origin: Option<u64>,
+) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    L: TryInto<LdConfig<'a>, Error = LdConfigError>,
+{
+    let addr = fixed_address(&cfg.try_into()?, region_origin)?;
+    write_address(path.as_ref(), name, addr)
+}
+
+fn write_address(path: &Path, name: &str, addr: u64) -> Result<(), Box<dyn Error>> {
+    let mut fp = File::create(path)?;
+    writeln!(
+        fp,
+        concat!(
+            "/* Generated by ",
+            env!("CARGO_PKG_NAME"),
+            " version ",
+            env!("CARGO_PKG_VERSION"),
+            " */"
+        )
+    )?;
+    writeln!(fp, "pub const {}: usize = 0x{:X};", name, addr)?;
+
+    Ok(())
+}
+
+/// Address of the link section placed by `cfg`, as seen by the linker.
+fn fixed_address(cfg: &LdConfig, region_origin: Option<u64>) -> Result<u64, Box<dyn Error>> {
+    match cfg.address {
+        Some(Address::Absolute(addr)) => Ok(addr),
+        Some(Address::RegionOffset(offset)) => region_origin
+            .ok_or("region origin is required to place the link section at a region offset")?
+            .checked_add(offset)
+            .ok_or_else(|| "link section address overflows".into()),
+        None => Err("link section is not placed at a fixed address".into()),
+    }
+}
+
 fn generate_script(cfg: LdConfig) -> Result<String, Box<dyn Error>> {
     let templ = Template::new(INFOMEM_LINKER_SCRIPT_TEMPLATE);
 
@@ -186,11 +566,35 @@ fn generate_header(data: &mut HashMap<&str, String>, _cfg: &LdConfig) {
 }
 
 fn generate_body(data: &mut HashMap<&str, String>, cfg: &LdConfig) {
+    let symbol = cfg.section.trim_start_matches('.');
+    data.insert("section", cfg.section.into());
+    data.insert("start_symbol", format!("_s{}", symbol));
+    data.insert("end_symbol", format!("_e{}", symbol));
+
     match cfg.alignment {
         None => data.insert("alignment", "".into()),
         Some(s) => data.insert("alignment", format!(". = ALIGN({});", s)),
     };
 
+    match cfg.section_align {
+        None => data.insert("section_align", "".into()),
+        Some(a) => data.insert("section_align", format!(" ALIGN(0x{:X})", a)),
+    };
+
+    match cfg.fill {
+        None => data.insert("fill", "".into()),
+        Some(f) => data.insert("fill", format!("FILL(0x{:02X});", f)),
+    };
+
+    match (cfg.address, cfg.region) {
+        (None, _) => data.insert("address", "".into()),
+        (Some(Address::Absolute(a)), _) => data.insert("address", format!("0x{:X} ", a)),
+        (Some(Address::RegionOffset(o)), Some(r)) => {
+            data.insert("address", format!("ORIGIN({}) + 0x{:X} ", r, o))
+        }
+        (Some(Address::RegionOffset(o)), None) => data.insert("address", format!("0x{:X} ", o)),
+    };
+
     match cfg.region {
         None => data.insert("memory_region", "".into()),
         Some(s) => data.insert("memory_region", format!("> {}", s)),
@@ -206,57 +610,125 @@ fn generate_body(data: &mut HashMap<&str, String>, cfg: &LdConfig) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::TestDir;
     use indoc::indoc;
     use ldscript_parser as lds;
 
+    // ldscript-parser doesn't know about "INSERT BEFORE/AFTER", so compare
+    // the trailing INSERT command separately.
+    fn split_insert(script: &str) -> (&str, Option<&str>) {
+        match script.rfind("INSERT ") {
+            Some(i) => (&script[..i], Some(script[i..].trim())),
+            None => (script, None),
+        }
+    }
+
     fn assert_ldscript_eq(left: &str, right: &str) -> Result<(), String> {
+        let (left, insl) = split_insert(left);
+        let (right, insr) = split_insert(right);
         let ldl = lds::parse(left)?;
         let ldr = lds::parse(right)?;
 
         assert_eq!(ldl, ldr);
+        assert_eq!(insl, insr);
 
         Ok(())
     }
 
-    #[cfg(all(target_os = "windows", target_env = "gnu"))]
     #[test]
     fn generate_hosted_windows_gnu() {
-        let cfg = HostedConfig::default().into();
+        let cfg = HostedConfig::default()
+            .into_ldconfig(&Target::new("windows", "gnu"))
+            .unwrap();
 
         let lds = generate_script(cfg).unwrap();
-        // FIXME: ldscript parser needs to be taught about "INSERT BEFORE/AFTER"...
-        assert_eq!(
+        assert_ldscript_eq(
             &lds,
             indoc! {"
-            
-            /* Generated by postcard-infomem-host version 0.1.0 */
-            
             SECTIONS {
                 . = ALIGN(__section_alignment__);
+                .pim : {
+                    _spim = .;
+                    KEEP(*(.pim))
+                    _epim = .;
+                }
+            } INSERT AFTER .text
+            "},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generate_hosted_linux() {
+        let cfg = HostedConfig::default()
+            .into_ldconfig(&Target::new("linux", "gnu"))
+            .unwrap();
+
+        let lds = generate_script(cfg).unwrap();
+        assert_ldscript_eq(
+            &lds,
+            indoc! {"
+            SECTIONS {
                 .postcard_infomem : {
                     _spostcard_infomem = .;
                     KEEP(*(.postcard_infomem))
                     _epostcard_infomem = .;
-                } 
-            } INSERT AFTER .text
+                }
+            } INSERT AFTER .rodata
             "},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generate_hosted_none() {
+        let err = HostedConfig::default()
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            LdConfigError::UnsupportedTarget {
+                config: "HostedConfig",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn generate_bare_hosted_target() {
+        let err = BareAppendConfig::default()
+            .into_ldconfig(&Target::new("linux", "gnu"))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "BareAppendConfig is not compatible with target_os = \"linux\", target_env = \"gnu\""
         );
     }
 
+    #[test]
+    fn generate_without_target_env() {
+        // Tests are not build scripts, so the target is unknown.
+        let err = LdConfig::try_from(BareSectionConfig::default()).unwrap_err();
+
+        assert!(matches!(
+            err,
+            LdConfigError::MissingEnvVar("CARGO_CFG_TARGET_OS", _)
+        ));
+    }
+
     #[test]
     fn generate_bare_append() {
-        let cfg = BareAppendConfig::default().into();
+        let cfg = BareAppendConfig::default()
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap();
 
         let lds = generate_script(cfg).unwrap();
-        // FIXME: ldscript parser needs to be taught about "INSERT BEFORE/AFTER"...
-        assert_eq!(
+        assert_ldscript_eq(
             &lds,
             indoc! {"
-            
-            /* Generated by postcard-infomem-host version 0.1.0 */
-
             SECTIONS {
-                
                 .postcard_infomem : {
                     _spostcard_infomem = .;
                     KEEP(*(.postcard_infomem))
@@ -264,14 +736,54 @@ mod tests {
                 } > FLASH
             } INSERT AFTER .rodata
             "},
-        );
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generate_bare_append_before_aligned() {
+        let cfg = BareAppendConfig::default()
+            .set_insert_before(".data")
+            .set_alignment(4)
+            .set_fill(0xff)
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap();
+
+        let lds = generate_script(cfg).unwrap();
+        assert_ldscript_eq(
+            &lds,
+            indoc! {"
+            SECTIONS {
+                .postcard_infomem : ALIGN(0x4) {
+                    FILL(0xFF);
+                    _spostcard_infomem = .;
+                    KEEP(*(.postcard_infomem))
+                    _epostcard_infomem = .;
+                } > FLASH
+            } INSERT BEFORE .data
+            "},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generate_bare_invalid_alignment() {
+        for align in [0, 3, 0x30] {
+            let err = BareSectionConfig::default()
+                .set_alignment(align)
+                .into_ldconfig(&Target::new("none", ""))
+                .unwrap_err();
+
+            assert!(matches!(err, LdConfigError::InvalidAlignment(a) if a == align));
+        }
     }
 
     #[test]
     fn generate_bare_section() {
         let cfg = BareSectionConfig::default()
             .set_memory_region("INFOMEM")
-            .into();
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap();
 
         let lds = generate_script(cfg).unwrap();
         assert_ldscript_eq(
@@ -288,4 +800,126 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn generate_bare_section_address() {
+        let cfg = BareSectionConfig::default()
+            .set_address(0x1000)
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap();
+
+        let lds = generate_script(cfg).unwrap();
+        assert_ldscript_eq(
+            &lds,
+            indoc! {"
+            SECTIONS {
+                .postcard_infomem 0x1000 : {
+                    _spostcard_infomem = .;
+                    KEEP(*(.postcard_infomem))
+                    _epostcard_infomem = .;
+                } > INFOMEM
+            }
+            "},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generate_bare_section_region_offset() {
+        let cfg = BareSectionConfig::default()
+            .set_region_offset(0x40)
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap();
+
+        let lds = generate_script(cfg).unwrap();
+        assert_ldscript_eq(
+            &lds,
+            indoc! {"
+            SECTIONS {
+                .postcard_infomem ORIGIN(INFOMEM) + 0x40 : {
+                    _spostcard_infomem = .;
+                    KEEP(*(.postcard_infomem))
+                    _epostcard_infomem = .;
+                } > INFOMEM
+            }
+            "},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn write_address() {
+        let dir = TestDir::new("postcard_infomem_write_address");
+        let path = dir.join("address.rs");
+        let cfg = BareSectionConfig::default().set_address(0x10001000);
+        // Tests are not build scripts, so the target is unknown.
+        assert!(write_address_const(&path, "INFOMEM_ADDR", cfg, None).is_err());
+
+        let target = Target::new("none", "");
+        let cfg = BareSectionConfig::default().set_address(0x10001000);
+        assert_eq!(
+            fixed_address(&cfg.into_ldconfig(&target).unwrap(), None).unwrap(),
+            0x10001000
+        );
+
+        let cfg = BareAppendConfig::default()
+            .set_region_offset(0x100)
+            .into_ldconfig(&target)
+            .unwrap();
+        assert_eq!(fixed_address(&cfg, Some(0x10000000)).unwrap(), 0x10000100);
+        assert!(fixed_address(&cfg, None).is_err());
+        assert!(fixed_address(&cfg, Some(u64::MAX)).is_err());
+
+        let cfg = BareSectionConfig::default().into_ldconfig(&target).unwrap();
+        assert!(fixed_address(&cfg, Some(0x10000000)).is_err());
+    }
+
+    #[test]
+    fn generate_bare_section_custom_name() {
+        let cfg = BareSectionConfig::default()
+            .set_section_name(".fw_info")
+            .set_memory_region("FW_INFO")
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap();
+
+        let lds = generate_script(cfg).unwrap();
+        assert_ldscript_eq(
+            &lds,
+            indoc! {"
+            SECTIONS {
+                .fw_info : {
+                    _sfw_info = .;
+                    KEEP(*(.fw_info))
+                    _efw_info = .;
+                } > FW_INFO
+            }
+            "},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn generate_bare_section_insert_after() {
+        let cfg = BareSectionConfig::default()
+            .set_insert_after(".text")
+            .set_address(0x1000)
+            .set_alignment(0x100)
+            .into_ldconfig(&Target::new("none", ""))
+            .unwrap();
+
+        let lds = generate_script(cfg).unwrap();
+        assert_ldscript_eq(
+            &lds,
+            indoc! {"
+            SECTIONS {
+                .postcard_infomem 0x1000 : ALIGN(0x100) {
+                    _spostcard_infomem = .;
+                    KEEP(*(.postcard_infomem))
+                    _epostcard_infomem = .;
+                } > INFOMEM
+            } INSERT AFTER .text
+            "},
+        )
+        .unwrap();
+    }
 }
@@ -1,22 +1,53 @@
 /*! Helper crate for [`InfoMem`] `struct`s intended to primarily be used in
 build scripts. */
 
+use std::collections::HashMap;
 use std::env;
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use bitflags::bitflags;
 use postcard::to_stdvec;
-use postcard_infomem::{to_stdvec_magic, InfoMem, Semver};
-use rustc_version::version_meta;
+use postcard_infomem::{to_stdvec_magic, Channel, InfoMem, Semver, HASH_PLACEHOLDER};
+use rustc_version::{version_meta, VersionMeta};
 use semver::Version;
+use serde::de::value::{Error as DeError, StrDeserializer};
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
+mod config;
+pub use config::{build_from_config, read_build_config, run_build_config, BuildConfig};
+
+mod extract;
+pub use extract::{read_default_infomem_section, read_infomem_section, scan_infomem};
+
+mod hardware;
+pub use hardware::{generate_hardware_info, write_hardware_info_to_file, HardwareConfig};
+
+mod hash;
+pub use hash::{patch_image_hash, HashConfig};
+
 mod ldscript;
-pub use ldscript::{generate_infomem_ldscript, BareAppendConfig, BareSectionConfig, HostedConfig};
+pub use ldscript::{
+    generate_infomem_ldscript, write_address_const, BareAppendConfig, BareSectionConfig,
+    HostedConfig, LdConfig, LdConfigError,
+};
+
+mod memory;
+pub use memory::{generate_memory_x, MemoryConfig};
+
+mod sign;
+pub use sign::{public_key_from_file, sign_infomem, write_signed_info_to_file};
+
+mod text;
+pub use text::{from_json, from_toml, to_json, to_toml};
+
+mod verify;
+pub use verify::{verify_infomem, VerifiedSection, VerifyConfig, VerifyError};
 
 // The short string will be fine.
 /** Workaround function to extract the short git SHA from `rustc -Vv`.
@@ -44,12 +75,22 @@ bitflags! {
 }
 
 /// Flags for default arguments to [`generate_from_env`].
-pub struct EnvConfig(EnvConfigFlags);
+pub struct EnvConfig {
+    flags: EnvConfigFlags,
+    utc_date: bool,
+    rustc: Option<PathBuf>,
+    rerun: bool,
+}
 
 impl Default for EnvConfig {
     /// Populate all [`InfoMem`] fields.
     fn default() -> Self {
-        Self(EnvConfigFlags::all())
+        Self {
+            flags: EnvConfigFlags::all(),
+            utc_date: false,
+            rustc: None,
+            rerun: true,
+        }
     }
 }
 
@@ -58,62 +99,205 @@ impl EnvConfig {
     to be used as a shortcut for enabling one or two flags with the remaining
     functions. */
     pub fn none() -> Self {
-        Self(EnvConfigFlags::empty())
+        Self {
+            flags: EnvConfigFlags::empty(),
+            ..Default::default()
+        }
     }
 
     /// If `true`, set [`AppInfo::name`](postcard_infomem::AppInfo::name).
     pub fn set_app_name(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::APP_NAME, op);
+        self.flags.set(EnvConfigFlags::APP_NAME, op);
         self
     }
 
     /// If `true`, set [`AppInfo::version`](postcard_infomem::AppInfo::version).
     pub fn set_app_version(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::APP_VERSION, op);
+        self.flags.set(EnvConfigFlags::APP_VERSION, op);
         self
     }
 
     /// If `true`, set [`AppInfo::git`](postcard_infomem::AppInfo::git).
     pub fn set_app_git(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::APP_GIT, op);
+        self.flags.set(EnvConfigFlags::APP_GIT, op);
         self
     }
 
     /// If `true`, set [`AppInfo::build_date`](postcard_infomem::AppInfo::build_date).
     pub fn set_app_date(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::APP_DATE, op);
+        self.flags.set(EnvConfigFlags::APP_DATE, op);
         self
     }
 
     /// If `true`, set [`RustcInfo::version`](postcard_infomem::RustcInfo::version).
     pub fn set_rustc_version(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::RUSTC_VERSION, op);
+        self.flags.set(EnvConfigFlags::RUSTC_VERSION, op);
         self
     }
 
     /// If `true`, set [`RustcInfo::llvm_version`](postcard_infomem::RustcInfo::llvm_version).
     pub fn set_rustc_llvm(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::RUSTC_LLVM, op);
+        self.flags.set(EnvConfigFlags::RUSTC_LLVM, op);
         self
     }
 
     /// If `true`, set [`RustcInfo::git`](postcard_infomem::RustcInfo::git).
     pub fn set_rustc_git(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::RUSTC_GIT, op);
+        self.flags.set(EnvConfigFlags::RUSTC_GIT, op);
         self
     }
 
     /// If `true`, set [`RustcInfo::host`](postcard_infomem::RustcInfo::host).
     pub fn set_rustc_host(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::RUSTC_HOST, op);
+        self.flags.set(EnvConfigFlags::RUSTC_HOST, op);
         self
     }
 
     /// If `true`, set [`RustcInfo::channel`](postcard_infomem::RustcInfo::channel).
     pub fn set_rustc_channel(mut self, op: bool) -> Self {
-        self.0.set(EnvConfigFlags::RUSTC_CHANNEL, op);
+        self.flags.set(EnvConfigFlags::RUSTC_CHANNEL, op);
+        self
+    }
+
+    /** If `true`, set [`AppInfo::build_date`](postcard_infomem::AppInfo::build_date)
+    to the current UTC time instead of the _local_ time. On Unix, the local
+    time cannot be determined in a multi-threaded process, such as `rustc`
+    running a procedural macro. */
+    pub fn set_app_date_utc(mut self, op: bool) -> Self {
+        self.utc_date = op;
         self
     }
+
+    /** Run the `rustc` at `path` to populate [`RustcInfo`](postcard_infomem::RustcInfo),
+    instead of the one named by the `RUSTC` environment variable (or `rustc`
+    if unset). */
+    pub fn set_rustc_path<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.rustc = Some(path.into());
+        self
+    }
+
+    /** If `true` (the default), tell Cargo when to rerun the build script
+    calling [`generate_from_env`]; see
+    [Rerunning the Build Script](generate_from_env#rerunning-the-build-script).
+    Set to `false` when not calling [`generate_from_env`] from a build script,
+    e.g. from a procedural macro. */
+    pub fn set_rerun_directives(mut self, op: bool) -> Self {
+        self.rerun = op;
+        self
+    }
+}
+
+/// Override variables of [`generate_from_env`], by the flag of the field they override.
+const OVERRIDES: [(EnvConfigFlags, &str); 9] = [
+    (EnvConfigFlags::APP_NAME, "PIM_APP_NAME"),
+    (EnvConfigFlags::APP_VERSION, "PIM_APP_VERSION"),
+    (EnvConfigFlags::APP_GIT, "PIM_APP_GIT"),
+    (EnvConfigFlags::APP_DATE, "PIM_BUILD_DATE"),
+    (EnvConfigFlags::RUSTC_VERSION, "PIM_RUSTC_VERSION"),
+    (EnvConfigFlags::RUSTC_LLVM, "PIM_RUSTC_LLVM_VERSION"),
+    (EnvConfigFlags::RUSTC_GIT, "PIM_RUSTC_GIT"),
+    (EnvConfigFlags::RUSTC_HOST, "PIM_RUSTC_HOST"),
+    (EnvConfigFlags::RUSTC_CHANNEL, "PIM_RUSTC_CHANNEL"),
+];
+
+/** Read the environment variable `var`, which overrides a field of
+[`InfoMem`]. An empty variable counts as unset. */
+fn read_override(var: &str) -> Result<Option<String>, env::VarError> {
+    match env::var(var) {
+        Ok(s) if s.is_empty() => Ok(None),
+        Ok(s) => Ok(Some(s)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/** Emit the `cargo:rerun-if-*` directives described in
+[Rerunning the Build Script](generate_from_env#rerunning-the-build-script). */
+fn emit_rerun_directives(cfg: &EnvConfig) {
+    if !cfg.rerun || env::var_os("OUT_DIR").is_none() {
+        return;
+    }
+
+    for (flag, var) in OVERRIDES {
+        if cfg.flags.contains(flag) {
+            println!("cargo:rerun-if-env-changed={}", var);
+        }
+    }
+
+    let manifest_dir = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => return,
+    };
+
+    for path in [manifest_dir.join("Cargo.toml"), manifest_dir.join("src")] {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    if cfg.flags.contains(EnvConfigFlags::APP_GIT) {
+        let git_dir = manifest_dir
+            .ancestors()
+            .map(|dir| dir.join(".git"))
+            .find(|git_dir| git_dir.join("HEAD").is_file());
+
+        if let Some(git_dir) = git_dir {
+            for path in git_head_paths(&git_dir) {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+    }
+}
+
+/** Return the files in `git_dir` which change when the checked out commit
+changes: `HEAD` itself, and if `HEAD` names a branch, the branch's ref.
+
+A branch's ref is either a loose file under `refs`, or an entry in
+`packed-refs`. Committing to a packed branch creates the loose file, so if it
+does not exist yet, its directory is returned instead. Paths which do not
+exist are omitted, since Cargo would otherwise rerun the build script every
+time. */
+fn git_head_paths(git_dir: &Path) -> Vec<PathBuf> {
+    let head = git_dir.join("HEAD");
+    let mut paths = Vec::new();
+
+    if let Some(branch) = fs::read_to_string(&head)
+        .ok()
+        .as_deref()
+        .and_then(|s| s.trim().strip_prefix("ref: "))
+    {
+        let loose = git_dir.join(branch);
+        if loose.is_file() {
+            paths.push(loose);
+        } else if let Some(dir) = loose.parent().filter(|dir| dir.is_dir()) {
+            paths.push(dir.to_path_buf());
+        }
+
+        let packed = git_dir.join("packed-refs");
+        if packed.is_file() {
+            paths.push(packed);
+        }
+    }
+
+    paths.push(head);
+    paths
+}
+
+/// Run [`version_meta`] on first use, so that `rustc` is not run if all of its
+/// fields are overridden.
+fn rustc_meta<'m>(
+    meta: &'m mut Option<VersionMeta>,
+    rustc: Option<&Path>,
+) -> Result<&'m VersionMeta, rustc_version::Error> {
+    if meta.is_none() {
+        *meta = Some(match rustc {
+            Some(path) => VersionMeta::for_command(Command::new(path))?,
+            None => version_meta()?,
+        });
+    }
+
+    Ok(meta.as_ref().unwrap())
 }
 
 /** Populate an [`InfoMem`] struct using environment variables and host [`Command`]s.
@@ -126,15 +310,18 @@ one field of an [`InfoMem`] `struct`.
 
 * [`AppInfo::name`](postcard_infomem::AppInfo::name): Query the `CARGO_PKG_NAME` environment variable.
 * [`AppInfo::version`](postcard_infomem::AppInfo::version): Query the `CARGO_PKG_VERSION` environment variable.
-* [`AppInfo::git`](postcard_infomem::AppInfo::git): Run `git describe --always --dirty --tags` and
-  capture the output. If this command fails to run (or fails to find a commit SHA),
-  the value becomes `Some("unknown")`.
-* [`AppInfo::build_date`](postcard_infomem::AppInfo::build_date): Use [`time`] to get the current _local_ time.
+* [`AppInfo::git`](postcard_infomem::AppInfo::git): Run `git describe --always --dirty --tags`
+  in `CARGO_MANIFEST_DIR` (or the current directory, if unset) and capture the
+  output. If this command fails to run (or fails to find a commit SHA), the
+  value becomes `Some("unknown")`.
+* [`AppInfo::build_date`](postcard_infomem::AppInfo::build_date): Use [`time`] to get the current _local_ time
+  (or UTC time, see [`EnvConfig::set_app_date_utc`]).
 
 ## [`rustc`](InfoMem::rustc)
 
 All fields of [`rustc`](InfoMem::rustc) are populated from the return value of
-[`version_meta`]. The [`RustcInfo::git`](postcard_infomem::RustcInfo::git)
+[`version_meta`], or of [`VersionMeta::for_command`] if
+[`EnvConfig::set_rustc_path`] was used. The [`RustcInfo::git`](postcard_infomem::RustcInfo::git)
 field will return `Option::None` if extracting the `rustc` `git` SHA fails.
 
 ## [`user`](InfoMem::user)
@@ -142,6 +329,51 @@ field will return `Option::None` if extracting the `rustc` `git` SHA fails.
 _This function does not modify [`user`](InfoMem::user) from the [default](InfoMem::default)
 value of `None`._ The user must populate this field through other means.
 
+## Overrides
+
+Each field can instead be set from an override environment variable, e.g. so
+that CI can inject a release version or the git SHA of a vendored source tree.
+An override is only consulted if the field's flag is enabled in [`EnvConfig`],
+and takes the place of the query described above. Empty variables are
+ignored. Like the version reported by `rustc`, `PIM_RUSTC_VERSION` is stored
+without its pre-release identifier (e.g. `-nightly`), which is already recorded
+in [`RustcInfo::channel`](postcard_infomem::RustcInfo::channel).
+
+| Field | Variable | Format |
+|-------|----------|--------|
+| [`AppInfo::name`](postcard_infomem::AppInfo::name) | `PIM_APP_NAME` | Any string |
+| [`AppInfo::version`](postcard_infomem::AppInfo::version) | `PIM_APP_VERSION` | Semver, e.g. `1.2.3-rc.1` |
+| [`AppInfo::git`](postcard_infomem::AppInfo::git) | `PIM_APP_GIT` | Any string |
+| [`AppInfo::build_date`](postcard_infomem::AppInfo::build_date) | `PIM_BUILD_DATE` | [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339), e.g. `2023-02-01T12:34:56Z` |
+| [`RustcInfo::version`](postcard_infomem::RustcInfo::version) | `PIM_RUSTC_VERSION` | Semver |
+| [`RustcInfo::llvm_version`](postcard_infomem::RustcInfo::llvm_version) | `PIM_RUSTC_LLVM_VERSION` | Semver |
+| [`RustcInfo::git`](postcard_infomem::RustcInfo::git) | `PIM_RUSTC_GIT` | Any string |
+| [`RustcInfo::host`](postcard_infomem::RustcInfo::host) | `PIM_RUSTC_HOST` | Any string |
+| [`RustcInfo::channel`](postcard_infomem::RustcInfo::channel) | `PIM_RUSTC_CHANNEL` | `dev`, `nightly`, `beta`, or `stable` |
+
+## Rerunning the Build Script
+
+When called from a build script, this function tells Cargo to rerun the build
+script if the inputs of the enabled fields change:
+
+* `cargo:rerun-if-env-changed` for the override variable of each enabled flag.
+* Since the above disables Cargo's default of rerunning the build script
+  whenever any file in the package changes, `cargo:rerun-if-changed` for the
+  package's `Cargo.toml` and `src` directory.
+* If [`AppInfo::git`](postcard_infomem::AppInfo::git) is enabled,
+  `cargo:rerun-if-changed` for the `HEAD` of the enclosing git repository (if
+  any), and for the ref of the checked out branch. The build script thus
+  reruns when a different commit is checked out, or a new commit is made on
+  the current branch. Uncommitted changes outside of the above files (which
+  `git describe --dirty` reports) do not cause a rerun.
+
+Build scripts which depend on other files should emit `cargo:rerun-if-changed`
+for them. Nothing is emitted if `OUT_DIR` is unset, since Cargo sets it for
+build scripts, or if disabled with [`EnvConfig::set_rerun_directives`]. Note
+that `OUT_DIR` is also set while compiling a crate that has a build script, so
+callers outside of build scripts, such as procedural macros, should disable
+the directives.
+
 # Arguments
 * `cfg`: Set of arguments that determine which fields of an [`InfoMem`]  that
   this function tries to set.
@@ -149,78 +381,137 @@ value of `None`._ The user must populate this field through other means.
 # Errors
 All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
 
-* [`VarError`](env::VarError): Returned if an environment variable does not exist.
+* [`VarError`](env::VarError): Returned if an environment variable does not
+  exist, or an override variable is not valid unicode.
 * [`semver::Error`]: Returned if any attempt to parse a [`Version`] fails.
+* [`time::error::Parse`]: Returned if parsing `PIM_BUILD_DATE` fails.
 * [`IndeterminateOffset`](time::error::IndeterminateOffset): Returned if getting the local time fails.
 * [`rustc_version::Error`]: Returned if [`version_meta`] fails to run for any reason.
+* [`serde::de::value::Error`](DeError): Returned if parsing `PIM_RUSTC_CHANNEL` fails.
 
 Notably _except for `git` fields_, [`generate_from_env`] will return an error
 if it fails to populate _any_ field corresponding to the enabled flags in [`EnvConfig`].
 */
 pub fn generate_from_env<'a>(cfg: EnvConfig) -> Result<InfoMem<'a>, Box<dyn Error>> {
+    emit_rerun_directives(&cfg);
+
+    let mut overrides = HashMap::new();
+    for (flag, var) in OVERRIDES {
+        if cfg.flags.contains(flag) {
+            if let Some(s) = read_override(var)? {
+                overrides.insert(var, s);
+            }
+        }
+    }
+
+    generate_from_lookup(cfg, |var| overrides.get(var).cloned())
+}
+
+/** Implementation of [`generate_from_env`], which looks up override variables
+with `overrides` instead of reading them from the environment. `overrides`
+returns `None` if a variable is unset. */
+fn generate_from_lookup<'a, F>(cfg: EnvConfig, overrides: F) -> Result<InfoMem<'a>, Box<dyn Error>>
+where
+    F: Fn(&str) -> Option<String>,
+{
     let mut im = InfoMem::default();
 
-    if cfg.0.contains(EnvConfigFlags::APP_NAME) {
-        im.app.name = Some(env::var("CARGO_PKG_NAME")?.into());
+    if cfg.flags.contains(EnvConfigFlags::APP_NAME) {
+        im.app.name = Some(match overrides("PIM_APP_NAME") {
+            Some(s) => s.into(),
+            None => env::var("CARGO_PKG_NAME")?.into(),
+        });
     }
 
-    if cfg.0.contains(EnvConfigFlags::APP_VERSION) {
+    if cfg.flags.contains(EnvConfigFlags::APP_VERSION) {
         // CARGO_PKG_VERSION comes from whatever is running this build script.
-        im.app.version = Some(Version::parse(&env::var("CARGO_PKG_VERSION")?)?.try_into()?);
+        let version = match overrides("PIM_APP_VERSION") {
+            Some(s) => s,
+            None => env::var("CARGO_PKG_VERSION")?,
+        };
+        im.app.version = Some(Version::parse(&version)?.try_into()?);
     }
 
     // Similar in spirit to https://github.com/fusion-engineering/rust-git-version,
     // except done at runtime of a build-script, not compile-time of a crate.
-    if cfg.0.contains(EnvConfigFlags::APP_GIT) {
-        im.app.git = match Command::new("git")
-            .args(["describe", "--always", "--dirty", "--tags"])
-            .output()
-        {
-            Ok(o) if o.status.success() => Some(match String::from_utf8(o.stdout) {
-                Ok(s) => s.into(),
-                Err(_) => "unknown".into(),
-            }),
-            _ => Some("unknown".into()),
+    if cfg.flags.contains(EnvConfigFlags::APP_GIT) {
+        im.app.git = match overrides("PIM_APP_GIT") {
+            Some(s) => Some(s.into()),
+            None => {
+                let mut git = Command::new("git");
+                git.args(["describe", "--always", "--dirty", "--tags"]);
+
+                // Build scripts already run in CARGO_MANIFEST_DIR, but procedural
+                // macros run wherever rustc was invoked.
+                if let Ok(dir) = env::var("CARGO_MANIFEST_DIR") {
+                    git.current_dir(dir);
+                }
+
+                match git.output() {
+                    Ok(o) if o.status.success() => Some(match String::from_utf8(o.stdout) {
+                        Ok(s) => s.into(),
+                        Err(_) => "unknown".into(),
+                    }),
+                    _ => Some("unknown".into()),
+                }
+            }
         };
     }
 
-    if cfg.0.contains(EnvConfigFlags::APP_DATE) {
-        im.app.build_date = Some(OffsetDateTime::now_local()?);
+    if cfg.flags.contains(EnvConfigFlags::APP_DATE) {
+        im.app.build_date = Some(match overrides("PIM_BUILD_DATE") {
+            Some(s) => OffsetDateTime::parse(&s, &Rfc3339)?,
+            None if cfg.utc_date => OffsetDateTime::now_utc(),
+            None => OffsetDateTime::now_local()?,
+        });
     }
 
-    if cfg.0.intersects(
-        EnvConfigFlags::RUSTC_VERSION
-            | EnvConfigFlags::RUSTC_LLVM
-            | EnvConfigFlags::RUSTC_GIT
-            | EnvConfigFlags::RUSTC_HOST
-            | EnvConfigFlags::RUSTC_CHANNEL,
-    ) {
-        let rv = version_meta()?;
+    let mut meta = None;
+    let rustc = cfg.rustc.as_deref();
 
-        if cfg.0.contains(EnvConfigFlags::RUSTC_VERSION) {
-            let mut sv: Semver = rv.semver.try_into()?;
-            sv.pre = None; //"-nightly", etc is already encoded in the Channel field.
-            im.rustc.version = Some(sv);
-        }
+    if cfg.flags.contains(EnvConfigFlags::RUSTC_VERSION) {
+        let version = match overrides("PIM_RUSTC_VERSION") {
+            Some(s) => Version::parse(&s)?,
+            None => rustc_meta(&mut meta, rustc)?.semver.clone(),
+        };
+        let mut sv: Semver = version.try_into()?;
+        sv.pre = None; //"-nightly", etc is already encoded in the Channel field.
+        im.rustc.version = Some(sv);
+    }
 
-        if cfg.0.contains(EnvConfigFlags::RUSTC_LLVM) {
-            im.rustc.llvm_version = rv
+    if cfg.flags.contains(EnvConfigFlags::RUSTC_LLVM) {
+        im.rustc.llvm_version = match overrides("PIM_RUSTC_LLVM_VERSION") {
+            Some(s) => Some(Version::parse(&s)?.try_into()?),
+            None => rustc_meta(&mut meta, rustc)?
                 .llvm_version
+                .as_ref()
                 .map(|l| Version::new(l.major, l.minor, 0).try_into())
-                .transpose()?;
-        }
+                .transpose()?,
+        };
+    }
 
-        if cfg.0.contains(EnvConfigFlags::RUSTC_GIT) {
-            im.rustc.git = extract_short_git_string(rv.short_version_string).map(Into::into);
-        }
+    if cfg.flags.contains(EnvConfigFlags::RUSTC_GIT) {
+        im.rustc.git = match overrides("PIM_RUSTC_GIT") {
+            Some(s) => Some(s.into()),
+            None => {
+                extract_short_git_string(rustc_meta(&mut meta, rustc)?.short_version_string.clone())
+                    .map(Into::into)
+            }
+        };
+    }
 
-        if cfg.0.contains(EnvConfigFlags::RUSTC_HOST) {
-            im.rustc.host = Some(rv.host.into());
-        }
+    if cfg.flags.contains(EnvConfigFlags::RUSTC_HOST) {
+        im.rustc.host = Some(match overrides("PIM_RUSTC_HOST") {
+            Some(s) => s.into(),
+            None => rustc_meta(&mut meta, rustc)?.host.clone().into(),
+        });
+    }
 
-        if cfg.0.contains(EnvConfigFlags::RUSTC_CHANNEL) {
-            im.rustc.channel = Some(rv.channel.into());
-        }
+    if cfg.flags.contains(EnvConfigFlags::RUSTC_CHANNEL) {
+        im.rustc.channel = Some(match overrides("PIM_RUSTC_CHANNEL") {
+            Some(s) => Channel::deserialize(StrDeserializer::<DeError>::new(&s))?,
+            None => rustc_meta(&mut meta, rustc)?.channel.into(),
+        });
     }
 
     Ok(im)
@@ -229,6 +520,7 @@ pub fn generate_from_env<'a>(cfg: EnvConfig) -> Result<InfoMem<'a>, Box<dyn Erro
 bitflags! {
     struct WriterConfigFlags: u8 {
         const HEADER = 1;
+        const IMAGE_HASH = 1 << 1;
     }
 }
 
@@ -242,13 +534,21 @@ impl WriterConfig {
         self.0.set(WriterConfigFlags::HEADER, op);
         self
     }
+
+    /** If `true`, reserve an [image hash block](postcard_infomem::HASH_PLACEHOLDER)
+    after the serialized [`InfoMem`], to be filled in after linking by
+    [`patch_image_hash`]. This requires the magic header. */
+    pub fn set_image_hash(mut self, op: bool) -> Self {
+        self.0.set(WriterConfigFlags::IMAGE_HASH, op);
+        self
+    }
 }
 
 impl Default for WriterConfig {
     /** By default, _enable_ writing the [magic header](postcard_infomem::ser::Magic)
-    before the serialized [`InfoMem`]. */
+    before the serialized [`InfoMem`], and do not reserve an image hash block. */
     fn default() -> Self {
-        Self(WriterConfigFlags::all())
+        Self(WriterConfigFlags::HEADER)
     }
 }
 
@@ -269,23 +569,69 @@ All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
 * [`io::Error`](std::io::Error): Returned if creating or writing the file fails.
 * [`postcard::Error`]: Returned if serializing `im` fails.
 
+An error is also returned if an image hash block is requested without the
+magic header.
 */
 pub fn write_info_to_file<P>(im: &InfoMem, path: P, cfg: WriterConfig) -> Result<(), Box<dyn Error>>
 where
     P: AsRef<Path>,
 {
-    let mut fp = File::create(path)?;
-
-    let buf = if cfg.0.contains(WriterConfigFlags::HEADER) {
+    let mut buf = if cfg.0.contains(WriterConfigFlags::HEADER) {
         to_stdvec_magic(&im)?
+    } else if cfg.0.contains(WriterConfigFlags::IMAGE_HASH) {
+        return Err("image hash block requires the magic header".into());
     } else {
         to_stdvec(&im)?
     };
 
+    if cfg.0.contains(WriterConfigFlags::IMAGE_HASH) {
+        buf.extend(HASH_PLACEHOLDER);
+    }
+
+    let mut fp = File::create(path)?;
+
     fp.write_all(&buf)?;
     Ok(())
 }
 
+/** A directory for one test, unique to this process so that concurrent test
+runs do not interfere. The directory is removed when dropped. */
+#[cfg(test)]
+pub(crate) struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let dir = env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -320,4 +666,71 @@ mod tests {
         let de = borrow(&ser);
         assert_eq!(im, de);
     }
+
+    #[test]
+    fn overrides() {
+        let lookup = |var: &str| {
+            let s = match var {
+                "PIM_APP_NAME" => "not-enabled",
+                "PIM_APP_GIT" => "vendor-abc1234",
+                "PIM_BUILD_DATE" => "2023-02-01T12:34:56Z",
+                "PIM_RUSTC_VERSION" => "1.70.0-beta.2",
+                "PIM_RUSTC_CHANNEL" => "beta",
+                _ => return None,
+            };
+            Some(s.to_string())
+        };
+
+        let cfg = EnvConfig::none()
+            .set_app_git(true)
+            .set_app_date(true)
+            .set_rustc_version(true)
+            .set_rustc_channel(true)
+            .set_rustc_host(true);
+        let im = generate_from_lookup(cfg, lookup).unwrap();
+
+        assert_eq!(im.app.name, None);
+        assert_eq!(im.app.git, Some("vendor-abc1234".into()));
+        assert_eq!(
+            im.app.build_date,
+            Some(OffsetDateTime::from_unix_timestamp(1675254896).unwrap())
+        );
+        assert_eq!(im.rustc.version.unwrap().to_string(), "1.70.0");
+        assert_eq!(im.rustc.channel, Some(Channel::Beta));
+        // Fields without an override are queried as usual.
+        assert_eq!(im.rustc.host, Some(version_meta().unwrap().host.into()));
+
+        let cfg = EnvConfig::none().set_app_date(true);
+        let bad_date = |_: &str| Some("yesterday".to_string());
+        assert!(generate_from_lookup(cfg, bad_date).is_err());
+    }
+
+    #[test]
+    fn git_head() {
+        let git_dir = TestDir::new("postcard_infomem_git_head");
+        let head = git_dir.join("HEAD");
+
+        // Detached HEAD.
+        fs::write(&head, "0123456789abcdef0123456789abcdef01234567\n").unwrap();
+        assert_eq!(git_head_paths(&git_dir), vec![head.clone()]);
+
+        // Branch without a loose ref or its directory.
+        fs::write(&head, "ref: refs/heads/feature/x\n").unwrap();
+        assert_eq!(git_head_paths(&git_dir), vec![head.clone()]);
+
+        // Packed branch; committing to it creates the loose ref.
+        let heads = git_dir.join("refs/heads/feature");
+        fs::create_dir_all(&heads).unwrap();
+        fs::write(git_dir.join("packed-refs"), "").unwrap();
+        assert_eq!(
+            git_head_paths(&git_dir),
+            [heads.clone(), git_dir.join("packed-refs"), head.clone()]
+        );
+
+        fs::write(heads.join("x"), "").unwrap();
+        assert_eq!(
+            git_head_paths(&git_dir),
+            [heads.join("x"), git_dir.join("packed-refs"), head]
+        );
+    }
 }
@@ -0,0 +1,259 @@
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Where the information memory region is carved out of flash.
+#[derive(Clone, Copy)]
+enum Carve {
+    /// At the end of flash; flash keeps its origin and shrinks.
+    End,
+    /// At the given address, which must be the start or end of flash.
+    At(u64),
+}
+
+/** Memory layout configuration for [`generate_memory_x`].
+
+The information memory region is carved out of the flash region, so that the
+two never overlap. By default, the `INFOMEM` region is placed at the end of
+the `FLASH` region, which matches the default memory region of
+[`BareSectionConfig`](crate::BareSectionConfig). Other regions, such as `RAM`,
+are passed through unchanged.
+*/
+pub struct MemoryConfig<'a> {
+    flash_name: &'a str,
+    flash_origin: u64,
+    flash_length: u64,
+    infomem_name: &'a str,
+    infomem_length: u64,
+    carve: Carve,
+    regions: Vec<(&'a str, u64, u64)>,
+}
+
+impl<'a> MemoryConfig<'a> {
+    /** Create a memory layout with `flash_length` bytes of flash at
+    `flash_origin`, `infomem_length` bytes of which are set aside for
+    information memory. */
+    pub fn new(flash_origin: u64, flash_length: u64, infomem_length: u64) -> Self {
+        Self {
+            flash_name: "FLASH",
+            flash_origin,
+            flash_length,
+            infomem_name: "INFOMEM",
+            infomem_length,
+            carve: Carve::End,
+            regions: Vec::new(),
+        }
+    }
+
+    /** Place the information memory region at `origin` instead of at the end
+    of flash. `origin` must be either the start of flash, or the end of flash
+    minus the length of the information memory region, since the remaining
+    flash must be contiguous. */
+    pub fn set_infomem_origin(mut self, origin: u64) -> Self {
+        self.carve = Carve::At(origin);
+        self
+    }
+
+    /// Set the name of the flash region, `FLASH` by default.
+    pub fn set_flash_region(mut self, name: &'a str) -> Self {
+        self.flash_name = name;
+        self
+    }
+
+    /** Set the name of the information memory region, `INFOMEM` by default.
+    This must match the memory region passed to
+    [`BareSectionConfig::set_memory_region`](crate::BareSectionConfig::set_memory_region). */
+    pub fn set_infomem_region(mut self, name: &'a str) -> Self {
+        self.infomem_name = name;
+        self
+    }
+
+    /// Add another memory region, e.g. `RAM`, to the generated `MEMORY` command.
+    pub fn add_region(mut self, name: &'a str, origin: u64, length: u64) -> Self {
+        self.regions.push((name, origin, length));
+        self
+    }
+}
+
+/** Write out a `memory.x` linker script with an information memory region
+carved out of flash, and tell Cargo where to find it.
+
+This is a convenience function intended to be used in a [build script](https://doc.rust-lang.org/cargo/reference/build-scripts.html),
+in place of a hand-written `memory.x`. Runtime crates such as `cortex-m-rt`
+`INCLUDE` `memory.x` from their own linker script, so no `-T` argument is
+passed for it. Combine this with [`generate_infomem_ldscript`](crate::generate_infomem_ldscript)
+and a [`BareSectionConfig`](crate::BareSectionConfig) to place the
+[`InfoMem`](postcard_infomem::InfoMem)s into the carved-out region:
+
+```ignore
+let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+let mem = MemoryConfig::new(0x10000000, 2048 * 1024, 0x1000)
+    .add_region("RAM", 0x20000000, 256 * 1024);
+
+generate_memory_x(out.join("memory.x"), mem).unwrap();
+generate_infomem_ldscript(out.join("info.x"), BareSectionConfig::default()).unwrap();
+```
+
+# Arguments
+* `path`: Name of file to write to, usually `memory.x` in `OUT_DIR`.
+* `cfg`: Memory layout configuration.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`io::Error`](std::io::Error): Returned if creating or writing the file fails.
+
+An error is also returned if the information memory region does not fit
+within flash, or is not placed at the start or end of flash, or if either
+region extends past the end of the address space.
+*/
+pub fn generate_memory_x<P>(path: P, cfg: MemoryConfig) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let dir = path
+        .as_ref()
+        .parent()
+        .ok_or("invalid path for memory script")?
+        .to_string_lossy();
+    let script = generate_memory(&cfg)?;
+    let mut fp = File::create(&path)?;
+    fp.write_all(script.as_bytes())?;
+
+    println!("cargo:rustc-link-search={}", dir);
+
+    Ok(())
+}
+
+fn generate_memory(cfg: &MemoryConfig) -> Result<String, Box<dyn Error>> {
+    if cfg.infomem_length == 0 || cfg.infomem_length >= cfg.flash_length {
+        return Err(format!(
+            "{} region (0x{:X} bytes) does not fit within {} region (0x{:X} bytes)",
+            cfg.infomem_name, cfg.infomem_length, cfg.flash_name, cfg.flash_length
+        )
+        .into());
+    }
+
+    let flash_end = cfg
+        .flash_origin
+        .checked_add(cfg.flash_length)
+        .ok_or_else(|| {
+            format!(
+                "{} region at 0x{:X} (0x{:X} bytes) overflows the address space",
+                cfg.flash_name, cfg.flash_origin, cfg.flash_length
+            )
+        })?;
+    let infomem_origin = match cfg.carve {
+        Carve::End => flash_end - cfg.infomem_length,
+        Carve::At(o) => o,
+    };
+
+    let flash_origin = if infomem_origin == cfg.flash_origin {
+        cfg.flash_origin + cfg.infomem_length
+    } else if infomem_origin.checked_add(cfg.infomem_length) == Some(flash_end) {
+        cfg.flash_origin
+    } else {
+        return Err(format!(
+            "{} region at 0x{:X} must be at the start or end of {} region",
+            cfg.infomem_name, infomem_origin, cfg.flash_name
+        )
+        .into());
+    };
+
+    let mut script = String::from(concat!(
+        "/* Generated by ",
+        env!("CARGO_PKG_NAME"),
+        " version ",
+        env!("CARGO_PKG_VERSION"),
+        " */\n\nMEMORY {\n"
+    ));
+
+    let regions = [
+        (
+            cfg.flash_name,
+            flash_origin,
+            cfg.flash_length - cfg.infomem_length,
+        ),
+        (cfg.infomem_name, infomem_origin, cfg.infomem_length),
+    ];
+    for (name, origin, length) in regions.iter().chain(cfg.regions.iter()) {
+        writeln!(
+            script,
+            "    {} : ORIGIN = 0x{:X}, LENGTH = 0x{:X}",
+            name, origin, length
+        )?;
+    }
+    script.push_str("}\n");
+
+    Ok(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use ldscript_parser as lds;
+
+    fn assert_ldscript_eq(left: &str, right: &str) {
+        assert_eq!(lds::parse(left).unwrap(), lds::parse(right).unwrap());
+    }
+
+    #[test]
+    fn memory_infomem_at_end() {
+        let cfg =
+            MemoryConfig::new(0x10000100, 0x1FFF00, 0x1000).add_region("RAM", 0x20000000, 0x40000);
+
+        assert_ldscript_eq(
+            &generate_memory(&cfg).unwrap(),
+            indoc! {"
+            MEMORY {
+                FLASH : ORIGIN = 0x10000100, LENGTH = 0x1FEF00
+                INFOMEM : ORIGIN = 0x101FF000, LENGTH = 0x1000
+                RAM : ORIGIN = 0x20000000, LENGTH = 0x40000
+            }
+            "},
+        );
+    }
+
+    #[test]
+    fn memory_infomem_at_start() {
+        let cfg = MemoryConfig::new(0xC000, 0x4000, 0x200)
+            .set_infomem_origin(0xC000)
+            .set_flash_region("ROM")
+            .set_infomem_region("FW_INFO");
+
+        assert_ldscript_eq(
+            &generate_memory(&cfg).unwrap(),
+            indoc! {"
+            MEMORY {
+                ROM : ORIGIN = 0xC200, LENGTH = 0x3E00
+                FW_INFO : ORIGIN = 0xC000, LENGTH = 0x200
+            }
+            "},
+        );
+    }
+
+    #[test]
+    fn memory_infomem_in_middle() {
+        let cfg = MemoryConfig::new(0xC000, 0x4000, 0x200).set_infomem_origin(0xD000);
+
+        assert!(generate_memory(&cfg).is_err());
+    }
+
+    #[test]
+    fn memory_overflow() {
+        let cfg = MemoryConfig::new(u64::MAX - 0xFF, 0x4000, 0x200);
+        assert!(generate_memory(&cfg).is_err());
+
+        let cfg = MemoryConfig::new(0xC000, 0x4000, 0x200).set_infomem_origin(u64::MAX - 0xFF);
+        assert!(generate_memory(&cfg).is_err());
+    }
+
+    #[test]
+    fn memory_infomem_too_large() {
+        let cfg = MemoryConfig::new(0xC000, 0x4000, 0x4000);
+
+        assert!(generate_memory(&cfg).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use ed25519_dalek::{Signer, SigningKey};
+use postcard_infomem::{split_signature, to_stdvec_magic, InfoMem, SIGNATURE_MAGIC};
+
+/** Read an Ed25519 private key from a key file, which must contain exactly
+the 32-byte secret key. */
+fn read_signing_key<P>(path: P) -> Result<SigningKey, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let bytes = fs::read(path)?;
+    let secret: [u8; 32] = bytes[..]
+        .try_into()
+        .map_err(|_| format!("key file must contain 32 bytes, found {}", bytes.len()))?;
+
+    Ok(SigningKey::from_bytes(&secret))
+}
+
+/** Append an Ed25519 signature block to a serialized [`InfoMem`].
+
+`data` must start with a serialized [`InfoMem`] preceded by a magic constant
+header, e.g. the output of [`to_stdvec_magic`], and must not already be signed.
+Any data following the serialized [`InfoMem`], such as a reserved image hash
+block, is kept after the signature block.
+The signature is verified by [`from_bytes_magic_signed`](postcard_infomem::from_bytes_magic_signed);
+see [`postcard_infomem`'s documentation](postcard_infomem::split_signature)
+for the layout.
+
+# Arguments
+* `data`: Serialized [`InfoMem`] to sign.
+* `key`: 32-byte Ed25519 secret key.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`postcard::Error`]: Returned if `data` does not contain a serialized [`InfoMem`].
+
+An error is also returned if `data` is already signed.
+*/
+pub fn sign_infomem(data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, Box<dyn Error>> {
+    sign_with(data, &SigningKey::from_bytes(key))
+}
+
+fn sign_with(data: &[u8], key: &SigningKey) -> Result<Vec<u8>, Box<dyn Error>> {
+    let signed = split_signature(data)?;
+    if signed.signature.is_some() {
+        return Err("InfoMem is already signed".into());
+    }
+
+    let mut buf = signed.record.to_vec();
+    buf.extend(SIGNATURE_MAGIC);
+    buf.extend(key.sign(signed.record).to_bytes());
+    buf.extend(signed.rest);
+
+    Ok(buf)
+}
+
+/** Return the Ed25519 public key corresponding to the secret key in
+`key_path`, e.g. to compile into a bootloader which verifies signed
+[`InfoMem`]s.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`io::Error`](std::io::Error): Returned if reading the key file fails.
+
+An error is also returned if the key file does not contain exactly 32 bytes.
+*/
+pub fn public_key_from_file<P>(key_path: P) -> Result<[u8; 32], Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    Ok(read_signing_key(key_path)?.verifying_key().to_bytes())
+}
+
+/** Write out a serialized [`InfoMem`] structure, followed by an Ed25519
+signature block, to file.
+
+This is the signed counterpart of [`write_info_to_file`](crate::write_info_to_file),
+intended to be used in a [build script](https://doc.rust-lang.org/cargo/reference/build-scripts.html)
+of a release pipeline. The magic header is always written, since the signature
+covers it.
+
+# Arguments
+* `im`: [`InfoMem`] `struct` to write out.
+* `path`: Name of file to write to.
+* `key_path`: Name of a key file containing exactly the 32-byte Ed25519 secret
+  key.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`io::Error`](std::io::Error): Returned if reading the key file, or creating
+  or writing the file fails.
+* [`postcard::Error`]: Returned if serializing `im` fails.
+
+An error is also returned if the key file does not contain exactly 32 bytes.
+*/
+pub fn write_signed_info_to_file<P, K>(
+    im: &InfoMem,
+    path: P,
+    key_path: K,
+) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    K: AsRef<Path>,
+{
+    let key = read_signing_key(key_path)?;
+    let buf = sign_with(&to_stdvec_magic(im)?, &key)?;
+
+    let mut fp = File::create(path)?;
+    fp.write_all(&buf)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestDir;
+
+    use postcard_infomem::{from_bytes_magic_signed, SignatureError};
+
+    #[test]
+    fn sign_round_trip() {
+        let dir = TestDir::new("postcard_infomem_sign_round_trip");
+        let key_path = dir.join("signing.key");
+        let out_path = dir.join("info.bin");
+        fs::write(&key_path, [7; 32]).unwrap();
+
+        let mut im: InfoMem = InfoMem::default();
+        im.app.name = Some("release".into());
+        write_signed_info_to_file(&im, &out_path, &key_path).unwrap();
+
+        let public = public_key_from_file(&key_path).unwrap();
+        let data = fs::read(&out_path).unwrap();
+        let de: InfoMem = from_bytes_magic_signed(&data, &public).unwrap();
+        assert_eq!(de, im);
+
+        let other = SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes();
+        assert_eq!(
+            from_bytes_magic_signed::<&[u8]>(&data, &other),
+            Err(SignatureError::Bad)
+        );
+    }
+
+    #[test]
+    fn sign_twice() {
+        let data = to_stdvec_magic(&InfoMem::<&[u8]>::default()).unwrap();
+        let signed = sign_infomem(&data, &[7; 32]).unwrap();
+
+        assert!(sign_infomem(&signed, &[7; 32]).is_err());
+        assert_eq!(
+            from_bytes_magic_signed::<&[u8]>(&data, &[0; 32]),
+            Err(SignatureError::Missing)
+        );
+    }
+
+    #[test]
+    fn bad_key_file() {
+        let dir = TestDir::new("postcard_infomem_bad_key_file");
+        let key_path = dir.join("signing.key");
+        fs::write(&key_path, [7; 16]).unwrap();
+
+        assert!(public_key_from_file(&key_path).is_err());
+    }
+}
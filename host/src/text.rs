@@ -0,0 +1,148 @@
+use std::error::Error;
+
+use postcard_infomem::InfoMem;
+use serde::Deserialize;
+
+/** Serialize an [`InfoMem`] structure to pretty-printed JSON.
+
+This is intended for reviewing or editing an [`InfoMem`] as text, e.g. as part
+of a release process. Versions are written as strings like `"1.2.3-pre+build"`,
+the `rustc` channel as a lowercase name like `"stable"`, dates as
+[RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) strings, and the
+[`user`](InfoMem::user) payload as an array of bytes.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`serde_json::Error`]: Returned if serializing `im` fails.
+*/
+pub fn to_json(im: &InfoMem) -> Result<String, Box<dyn Error>> {
+    Ok(serde_json::to_string_pretty(im)?)
+}
+
+/** Parse an [`InfoMem`] structure from JSON, in the format written by
+[`to_json`].
+
+Missing optional fields are [`Option::None`]. Since the [`user`](InfoMem::user)
+payload cannot be borrowed from JSON, it is returned in a [`Vec<u8>`]; use
+[`InfoMem::as_borrowed`] to pass the result to e.g.
+[`write_info_to_file`](crate::write_info_to_file).
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`serde_json::Error`]: Returned if parsing `s` fails.
+*/
+pub fn from_json<'a>(s: &'a str) -> Result<InfoMem<'a, Vec<u8>>, Box<dyn Error>> {
+    Ok(serde_json::from_str(s)?)
+}
+
+/** Serialize an [`InfoMem`] structure to TOML.
+
+The format is analogous to [`to_json`]. Fields which are [`Option::None`] are
+omitted, since TOML has no null value.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`toml::ser::Error`]: Returned if serializing `im` fails.
+*/
+pub fn to_toml(im: &InfoMem) -> Result<String, Box<dyn Error>> {
+    Ok(toml::to_string_pretty(im)?)
+}
+
+/** Parse an [`InfoMem`] structure from TOML, in the format written by
+[`to_toml`].
+
+The TOML parser does not borrow from its input, so the returned [`InfoMem`]
+owns all of its data. See [`from_json`] for how to use the result.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`toml::de::Error`]: Returned if parsing `s` fails.
+*/
+pub fn from_toml(s: &str) -> Result<InfoMem<'static, Vec<u8>>, Box<dyn Error>> {
+    Ok(InfoMem::deserialize(toml::Deserializer::new(s))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use postcard_infomem::{from_bytes_magic, to_stdvec_magic, Channel, InfoStr, Semver};
+    use time::{OffsetDateTime, UtcOffset};
+
+    fn info() -> InfoMem<'static> {
+        let mut im: InfoMem = InfoMem::default();
+        im.app.name = Some("text".into());
+        im.app.version = Some(Semver {
+            major: 1,
+            minor: 2,
+            patch: 3,
+            pre: Some("rc.1".into()),
+            build: Some("abc".into()),
+        });
+        im.app.git = Some("v1.2.3-rc.1\n".into());
+        im.app.build_date = Some(
+            OffsetDateTime::from_unix_timestamp(1675272896)
+                .unwrap()
+                .to_offset(UtcOffset::from_hms(-5, 0, 0).unwrap()),
+        );
+        im.rustc.channel = Some(Channel::Nightly);
+        im.user = Some(&[0, 1, 0xff]);
+        im
+    }
+
+    #[test]
+    fn round_trip_json() {
+        let im = info();
+
+        let json = to_json(&im).unwrap();
+        assert!(json.contains(r#""version": "1.2.3-rc.1+abc""#));
+        assert!(json.contains(r#""build_date": "2023-02-01T12:34:56-05:00""#));
+        assert!(json.contains(r#""channel": "nightly""#));
+
+        let de = from_json(&json).unwrap();
+        assert_eq!(de.as_borrowed(), im);
+    }
+
+    #[test]
+    fn round_trip_toml() {
+        let im = info();
+
+        let toml = to_toml(&im).unwrap();
+        let de = from_toml(&toml).unwrap();
+        assert_eq!(de.as_borrowed(), im);
+    }
+
+    #[test]
+    fn edited_toml_to_blob() {
+        let toml = indoc! {r#"
+            version = "0.1.0"
+            user = [1, 2, 3]
+
+            [app]
+            name = "edited"
+            version = "2.0.0"
+            build_date = "2023-03-04T05:06:07Z"
+
+            [rustc]
+            channel = "stable"
+        "#};
+
+        let de = from_toml(toml).unwrap();
+        let blob = to_stdvec_magic(&de.as_borrowed()).unwrap();
+        let im: InfoMem = from_bytes_magic(&blob).unwrap();
+
+        assert_eq!(im.app.name, Some(InfoStr::Borrowed("edited")));
+        assert_eq!(
+            im.app.build_date,
+            Some(OffsetDateTime::from_unix_timestamp(1677906367).unwrap())
+        );
+        assert_eq!(im.rustc.channel, Some(Channel::Stable));
+        assert_eq!(im.rustc.git, None);
+        assert_eq!(im.user, Some(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn bad_version() {
+        assert!(from_toml("version = \"1.2\"\n[app]\n[rustc]\n").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use object::ObjectSection;
+use postcard_infomem::from_bytes_magic;
+
+use crate::extract::{default_section_name, find_section};
+
+/** Post-link verification configuration for [`verify_infomem`].
+
+By default, only the link section that `include_postcard_infomem` uses by
+default for the format of the binary (`.postcard_infomem` for ELF, `.pim` for
+PE/COFF, and `__DATA,__pim` for Mach-O) is checked for existence, size, and
+contents. */
+#[derive(Default)]
+pub struct VerifyConfig<'a> {
+    section: Option<&'a str>,
+    region: Option<(u64, u64)>,
+}
+
+impl<'a> VerifyConfig<'a> {
+    /** Set the name of the link section to verify, which must match the
+    `section` option passed to `include_postcard_infomem`. Mach-O sections
+    may be qualified by their segment, e.g. `__DATA,__pim`. */
+    pub fn set_section_name(mut self, name: &'a str) -> Self {
+        self.section = Some(name);
+        self
+    }
+
+    /** Require the link section to lie entirely within `length` bytes
+    starting at `origin`.
+
+    Memory region names do not survive linking, so the region must be given by
+    address, e.g. using the same values passed to
+    [`MemoryConfig`](crate::MemoryConfig). */
+    pub fn set_region(mut self, origin: u64, length: u64) -> Self {
+        self.region = Some((origin, length));
+        self
+    }
+}
+
+/// Link section which passed verification by [`verify_infomem`].
+#[derive(Debug)]
+pub struct VerifiedSection {
+    /// Address of the link section.
+    pub address: u64,
+    /// Contents of the link section.
+    pub data: Vec<u8>,
+}
+
+/** Error returned when a link section fails verification.
+
+Returned by [`verify_infomem`], casted to [`Box<dyn Error>`]. */
+#[derive(Debug)]
+pub enum VerifyError {
+    /** The link section does not exist. This usually means that the linker
+    garbage-collected it, because the linker script does not `KEEP` it. */
+    MissingSection(String),
+    /// The link section exists, but is empty.
+    EmptySection(String),
+    /// The link section does not lie within the required region.
+    OutOfRegion {
+        /// Name of the link section.
+        section: String,
+        /// Address of the link section.
+        address: u64,
+        /// Size of the link section.
+        size: u64,
+        /// Origin of the required region.
+        origin: u64,
+        /// Length of the required region.
+        length: u64,
+    },
+    /// The contents of the link section could not be deserialized.
+    Decode(postcard::Error),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::MissingSection(s) => write!(f, "section {} not found", s),
+            VerifyError::EmptySection(s) => write!(f, "section {} is empty", s),
+            VerifyError::OutOfRegion {
+                section,
+                address,
+                size,
+                origin,
+                length,
+            } => write!(
+                f,
+                "section {} (0x{:X}, 0x{:X} bytes) does not fit within region (0x{:X}, 0x{:X} bytes)",
+                section, address, size, origin, length
+            ),
+            VerifyError::Decode(e) => write!(f, "could not deserialize InfoMem: {}", e),
+        }
+    }
+}
+
+impl Error for VerifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VerifyError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/** Verify that a linked binary contains a usable link section of serialized
+[`InfoMem`](postcard_infomem::InfoMem)s.
+
+This is intended to be used as a CI gate after linking, since a misconfigured
+linker script can silently drop or misplace the link section. The following
+checks are performed, in order:
+
+1. The link section exists.
+2. The link section is non-empty.
+3. If [`VerifyConfig::set_region`] was used, the link section lies entirely
+   within the region.
+4. The start of the link section deserializes with [`from_bytes_magic`].
+
+On success, the address and contents of the link section are returned, which
+can be passed to [`scan_infomem`](crate::scan_infomem).
+
+# Arguments
+* `path`: Name of the linked binary to verify.
+* `cfg`: Verification configuration.
+
+# Errors
+All errors are casted to [`Box<dyn Error>`]. Concrete error types include:
+* [`VerifyError`]: Returned if any of the above checks fail.
+* [`io::Error`](std::io::Error): Returned if reading the file fails.
+* [`object::Error`]: Returned if parsing the file or section fails.
+*/
+pub fn verify_infomem<P>(path: P, cfg: VerifyConfig) -> Result<VerifiedSection, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let data = fs::read(path)?;
+    let file = object::File::parse(&*data)?;
+    let section = cfg
+        .section
+        .unwrap_or_else(|| default_section_name(file.format()));
+    let sect =
+        find_section(&file, section).ok_or_else(|| VerifyError::MissingSection(section.into()))?;
+
+    let (address, size) = (sect.address(), sect.size());
+    if size == 0 {
+        return Err(VerifyError::EmptySection(section.into()).into());
+    }
+
+    if let Some((origin, length)) = cfg.region {
+        // A section or region wrapping past the end of the address space
+        // cannot be contained in anything.
+        let contained = match (address.checked_add(size), origin.checked_add(length)) {
+            (Some(end), Some(region_end)) => address >= origin && end <= region_end,
+            _ => false,
+        };
+
+        if !contained {
+            return Err(VerifyError::OutOfRegion {
+                section: section.into(),
+                address,
+                size,
+                origin,
+                length,
+            }
+            .into());
+        }
+    }
+
+    let data = sect.data()?.to_vec();
+    from_bytes_magic::<&[u8]>(&data).map_err(VerifyError::Decode)?;
+
+    Ok(VerifiedSection { address, data })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postcard_infomem::InfoMem;
+
+    // Assembled from fixtures/thumbv6m.s with llvm-mc, and linked from
+    // fixtures/thumbv6m.x with GNU ld -n:
+    //   llvm-mc -triple=thumbv6m-none-eabi -filetype=obj -o thumbv6m.o thumbv6m.s
+    //   arm-none-eabi-ld -n -T thumbv6m.x -o thumbv6m.elf thumbv6m.o
+    // .postcard_infomem contains one InfoMem with app.name = "fixture", padded
+    // to 0x40 bytes with 0xff.
+    const THUMBV6M: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/thumbv6m.elf");
+
+    #[test]
+    fn verify_ok() {
+        let cfg = VerifyConfig::default().set_region(0x10001000, 0x100);
+        let sect = verify_infomem(THUMBV6M, cfg).unwrap();
+
+        assert_eq!(sect.address, 0x10001000);
+        assert_eq!(sect.data.len(), 0x40);
+
+        let im: InfoMem = from_bytes_magic(&sect.data).unwrap();
+        assert_eq!(im.app.name, Some("fixture".into()));
+    }
+
+    #[test]
+    fn verify_default_section() {
+        for name in ["x86_64-windows.exe", "x86_64-macos.o"] {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/").to_owned() + name;
+            let sect = verify_infomem(path, VerifyConfig::default()).unwrap();
+
+            let im: InfoMem = from_bytes_magic(&sect.data).unwrap();
+            assert_eq!(im.app.name, Some("fixture".into()));
+        }
+    }
+
+    #[test]
+    fn verify_missing() {
+        let cfg = VerifyConfig::default().set_section_name(".fw_info");
+        let err = verify_infomem(THUMBV6M, cfg).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(VerifyError::MissingSection(_))
+        ));
+    }
+
+    #[test]
+    fn verify_out_of_region() {
+        let cfg = VerifyConfig::default().set_region(0x10001000, 0x20);
+        let err = verify_infomem(THUMBV6M, cfg).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(VerifyError::OutOfRegion { .. })
+        ));
+
+        let cfg = VerifyConfig::default().set_region(0x10001000, u64::MAX);
+        let err = verify_infomem(THUMBV6M, cfg).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(VerifyError::OutOfRegion { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_decode() {
+        let cfg = VerifyConfig::default().set_section_name(".shstrtab");
+        let err = verify_infomem(THUMBV6M, cfg).unwrap_err();
+
+        assert!(matches!(err.downcast_ref(), Some(VerifyError::Decode(_))));
+    }
+}
@@ -0,0 +1,125 @@
+/*! Procedural macros for embedding [`InfoMem`](../postcard_infomem/struct.InfoMem.html)
+`struct`s into a binary without a build script.
+
+This crate is intended to be used through the `macros` feature of
+[`postcard-infomem-device`](../postcard_infomem_device/index.html), which
+re-exports its macros.
+*/
+
+use std::env;
+use std::error::Error;
+use std::path::PathBuf;
+
+use postcard_infomem::to_stdvec_magic;
+use postcard_infomem_host::{generate_from_env, EnvConfig};
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::quote;
+
+#[proc_macro]
+/** Create a `static` variable holding a serialized [`InfoMem`](../postcard_infomem/struct.InfoMem.html)
+structure, populated from the environment of the crate being compiled.
+
+This macro is a one-line alternative to calling [`generate_from_env`] and
+[`write_info_to_file`](../postcard_infomem_host/fn.write_info_to_file.html)
+from a build script, and then invoking [`include_postcard_infomem`](../postcard_infomem_device/macro.include_postcard_infomem.html)
+on the generated file. It can be invoked in one of two ways:
+
+* ```ignore
+  # use postcard_infomem_device::postcard_infomem;
+  postcard_infomem!();
+  ```
+* ```ignore
+  # use postcard_infomem_device::postcard_infomem;
+  postcard_infomem!(generated_module_name, capacity = 256);
+  ```
+
+If `generated_module_name` is omitted, it defaults to `infomem`. The generated
+module, and any options following `generated_module_name`, are identical to
+those of [`include_postcard_infomem`](../postcard_infomem_device/macro.include_postcard_infomem.html).
+All fields of the [`InfoMem`](../postcard_infomem/struct.InfoMem.html) are
+populated as if by `generate_from_env(EnvConfig::default())`, except that:
+
+* [`AppInfo::build_date`](../postcard_infomem/struct.AppInfo.html#structfield.build_date)
+  is in UTC, since `rustc` is multi-threaded and the local time cannot be
+  determined on Unix.
+* [`RustcInfo`](../postcard_infomem/struct.RustcInfo.html) describes the
+  `rustc` which is expanding this macro. If the macro is expanded by another
+  tool (e.g. rust-analyzer), the `rustc` found as in a build script is used.
+
+# Rebuilds
+Unlike a build script, this macro cannot tell Cargo when its output is out of
+date. Fields such as [`AppInfo::git`](../postcard_infomem/struct.AppInfo.html#structfield.git)
+and [`AppInfo::build_date`](../postcard_infomem/struct.AppInfo.html#structfield.build_date)
+are only refreshed when the invoking crate is recompiled for another reason.
+*/
+pub fn postcard_infomem(input: TokenStream) -> TokenStream {
+    expand(input.into()).into()
+}
+
+fn expand(input: TokenStream2) -> TokenStream2 {
+    let args = if input.is_empty() {
+        quote!(infomem)
+    } else {
+        input
+    };
+
+    match generate() {
+        Ok(bytes) => {
+            let data = Literal::byte_string(&bytes);
+            quote!(::postcard_infomem_device::__postcard_infomem_static!(#data, #args);)
+        }
+        Err(e) => {
+            let msg = format!("failed to generate InfoMem: {}", e);
+            quote!(::core::compile_error!(#msg);)
+        }
+    }
+}
+
+/// The compiler expanding this macro, if it is `rustc` itself.
+fn current_rustc() -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    let is_rustc = exe.file_stem()?.to_str()?.starts_with("rustc");
+
+    is_rustc.then_some(exe)
+}
+
+fn generate() -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut cfg = EnvConfig::default()
+        .set_app_date_utc(true)
+        .set_rerun_directives(false);
+    if let Some(rustc) = current_rustc() {
+        cfg = cfg.set_rustc_path(rustc);
+    }
+
+    let im = generate_from_env(cfg)?;
+    Ok(to_stdvec_magic(&im)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_default_module() {
+        let out = expand(TokenStream2::new()).to_string();
+
+        assert!(out.contains("__postcard_infomem_static"));
+        assert!(out.contains("infomem"));
+    }
+
+    #[test]
+    fn expand_succeeds() {
+        // Test threads make this process multi-threaded, like rustc.
+        let out = expand(TokenStream2::new()).to_string();
+
+        assert!(!out.contains("compile_error"), "{}", out);
+    }
+
+    #[test]
+    fn expand_with_options() {
+        let out = expand(quote!(app_infomem, capacity = 256)).to_string();
+
+        assert!(out.contains("app_infomem , capacity = 256"));
+    }
+}
@@ -0,0 +1,164 @@
+/*! Module implementing a user payload made of multiple tagged entries.
+
+Independent pieces of user-defined data (e.g. board calibration, manufacturing
+data, and feature licenses) can share the single [`user`](InfoMem::user)
+payload of an [`InfoMem`] as a list of [`Entry`]s, each identified by an `id`.
+The list is serialized as a [`postcard`] sequence, either into a byte blob
+(e.g. with [`postcard::to_stdvec`]), or directly with [`Typed`]:
+
+```
+# use postcard_infomem::{Entry, InfoMem, Typed};
+let entries = [
+    Entry { id: 1, data: b"calibration" },
+    Entry { id: 2, data: b"license" },
+];
+
+let im: InfoMem<Typed<&[Entry]>> = InfoMem {
+    user: Some(Typed(&entries)),
+    ..Default::default()
+};
+```
+
+Entries are looked up by `id` with [`find_entry`] or [`find_entry_seq`],
+without copying or deserializing the contents of the other entries.
+*/
+
+use core::iter::Take;
+use core::result::Result as CoreResult;
+
+use postcard::{take_from_bytes, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// A tagged entry of a user payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry<'a> {
+    /// Identifier of this entry, unique within the user payload.
+    pub id: u32,
+    #[serde(borrow)]
+    /// Contents of this entry.
+    pub data: &'a [u8],
+}
+
+/** Find the contents of the entry with `id` in a user payload containing a
+serialized list of [`Entry`]s.
+
+# Errors
+Returns a [`postcard::Error`] if `user` does not contain a serialized list of
+[`Entry`]s. A missing entry is _not_ an error.
+*/
+pub fn find_entry(user: &[u8], id: u32) -> Result<Option<&[u8]>> {
+    let (count, mut rest) = take_from_bytes::<usize>(user)?;
+
+    for _ in 0..count {
+        let (entry, r) = take_from_bytes::<Entry>(rest)?;
+        if entry.id == id {
+            return Ok(Some(entry.data));
+        }
+
+        rest = r;
+    }
+
+    Ok(None)
+}
+
+/** Find the entry with `id` in a user payload read sequentially, and return a
+reader bounded to its contents.
+
+`payload` is typically the user payload as bounded by [`Deferred::take`].
+Entries before the one found are skipped byte-by-byte, so no buffer is needed.
+
+# Errors
+Returns a [`postcard::Error`] if `payload` does not contain a serialized list
+of [`Entry`]s, or if reading from `payload` fails. A missing entry is _not_ an
+error.
+*/
+pub fn find_entry_seq<R>(payload: R, id: u32) -> Result<Option<Take<R>>>
+where
+    R: Iterator<Item = CoreResult<u8, SequentialReadError>>,
+{
+    // Only varints are deserialized, so no buffer is needed for Seq.
+    let (count, mut payload) = take_from_seq::<_, _, usize>(payload, &mut [][..])?;
+
+    for _ in 0..count {
+        let ((entry_id, len), mut rest) =
+            take_from_seq::<_, _, (u32, usize)>(payload, &mut [][..])?;
+        if entry_id == id {
+            return Ok(Some(rest.take(len)));
+        }
+
+        for _ in 0..len {
+            rest.next()
+                .ok_or(postcard::Error::DeserializeUnexpectedEnd)?
+                .map_err(|_| postcard::Error::DeserializeUnexpectedEnd)?;
+        }
+
+        payload = rest;
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postcard::to_allocvec;
+
+    extern crate std;
+    use std::vec::Vec;
+
+    const ENTRIES: [Entry; 3] = [
+        Entry {
+            id: 1,
+            data: b"calibration",
+        },
+        Entry {
+            id: 0x1000,
+            data: b"manufacturing",
+        },
+        Entry { id: 3, data: b"" },
+    ];
+
+    fn seq(v: Vec<u8>) -> impl Iterator<Item = CoreResult<u8, SequentialReadError>> {
+        v.into_iter().map(Ok)
+    }
+
+    #[test]
+    fn find_in_slice() {
+        let user = to_allocvec(&ENTRIES[..]).unwrap();
+
+        assert_eq!(find_entry(&user, 0x1000), Ok(Some(&b"manufacturing"[..])));
+        assert_eq!(find_entry(&user, 3), Ok(Some(&b""[..])));
+        assert_eq!(find_entry(&user, 2), Ok(None));
+        assert!(find_entry(&user[..user.len() - 3], 2).is_err());
+    }
+
+    #[test]
+    fn find_in_seq() {
+        let im: InfoMem<Typed<&[Entry]>> = InfoMem {
+            user: Some(Typed(&ENTRIES)),
+            ..Default::default()
+        };
+
+        let mut buf = [0; 16];
+        let ser = to_allocvec_magic(&im).unwrap();
+        let (im_de, mut rest) = from_seq_magic_deferred(seq(ser), &mut buf).unwrap();
+        let user = im_de.user.unwrap();
+
+        let entry = find_entry_seq(user.take(rest.by_ref()), 0x1000)
+            .unwrap()
+            .unwrap()
+            .collect::<CoreResult<Vec<u8>, _>>()
+            .unwrap();
+        assert_eq!(entry, b"manufacturing");
+    }
+
+    #[test]
+    fn find_in_seq_missing() {
+        let user = to_allocvec(&ENTRIES[..]).unwrap();
+
+        assert!(find_entry_seq(seq(user.clone()), 2).unwrap().is_none());
+        assert!(find_entry_seq(seq(user[..user.len() - 3].to_vec()), 2).is_err());
+    }
+}
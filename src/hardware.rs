@@ -0,0 +1,210 @@
+/*! Module implementing a hardware identity record.
+
+An [`InfoMem`] describes a software build, and is identical for every board
+that the build is flashed onto. A [`HardwareInfo`] describes one particular
+board instead, and is intended to be written at production time (e.g. into
+OTP, EEPROM, or a reserved flash page) by a factory tool using
+[`postcard_infomem_host`](../postcard_infomem_host/index.html), and read by the
+firmware at runtime.
+
+A [`HardwareInfo`] is serialized on its own, preceded by the
+[hardware magic header](HARDWARE_MAGIC), rather than as part of an
+[`InfoMem`].
+*/
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+extern crate alloc;
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use postcard::de_flavors;
+use postcard::ser_flavors::Slice;
+use postcard::{serialize_with_flavor, Deserializer, Result};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[cfg(feature = "alloc")]
+use postcard::ser_flavors::AllocVec;
+
+use crate::de::Seq;
+use crate::magic::{de, ser};
+use crate::*;
+
+/** Magic constant header prepended to a serialized [`HardwareInfo`].
+
+End with 0x80 to avoid the temptation to serialize as UTF-8 string. */
+pub const HARDWARE_MAGIC: [u8; 4] = [b'P', b'I', b'B', 0x80];
+
+/// Hardware address of a board.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HardwareAddress {
+    /// 48-bit MAC address.
+    Mac([u8; 6]),
+    /// 64-bit extended unique identifier.
+    Eui64([u8; 8]),
+}
+
+/** Information about the board that an application runs on.
+
+The [`Default`] implementation provides a hardcoded [`HardwareInfo::version`],
+and [`Option::None`] for all remaining `struct` members. _This crate does not
+attempt to populate this `struct`._
+*/
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct HardwareInfo<'a> {
+    /** Version of this `struct` that was deserialized or created, hardcoded
+    during crate compilation. See [`InfoMem::version`].
+
+    _This member must always remain first, even between major versions_. */
+    pub version: Semver<'a>,
+    #[serde(borrow)]
+    /// Name of the board.
+    pub board: Option<InfoStr<'a>>,
+    #[serde(borrow)]
+    /// Hardware revision of the board.
+    pub revision: Option<InfoStr<'a>>,
+    #[serde(borrow)]
+    /// Serial number of the board.
+    pub serial: Option<InfoStr<'a>>,
+    /// Hardware address assigned to the board.
+    pub address: Option<HardwareAddress>,
+    #[serde(default, with = "crate::shim::date")]
+    /// Manufacturing date of the board.
+    pub manufacture_date: Option<OffsetDateTime>,
+}
+
+impl<'a> Default for HardwareInfo<'a> {
+    fn default() -> Self {
+        Self {
+            version: Semver::this_version(),
+            board: None,
+            revision: None,
+            serial: None,
+            address: None,
+            manufacture_date: None,
+        }
+    }
+}
+
+/** Serialize [`HardwareInfo`] into a [`slice`] with a magic constant header.
+
+This function is analogous to [`to_slice_magic`]. */
+pub fn to_slice_hardware<'b>(value: &HardwareInfo, buf: &'b mut [u8]) -> Result<&'b mut [u8]> {
+    let magic = ser::Magic::try_with_header(Slice::new(buf), &HARDWARE_MAGIC)?;
+    serialize_with_flavor(value, magic)
+}
+
+#[cfg(feature = "alloc")]
+/** Serialize [`HardwareInfo`] into a [`Vec`] with a magic constant header.
+
+This function is analogous to [`to_allocvec_magic`]. */
+pub fn to_allocvec_hardware(value: &HardwareInfo) -> Result<Vec<u8>> {
+    let magic = ser::Magic::try_with_header(AllocVec::default(), &HARDWARE_MAGIC)?;
+    serialize_with_flavor(value, magic)
+}
+
+#[cfg(feature = "std")]
+pub use to_allocvec_hardware as to_stdvec_hardware;
+
+/** Deserialize [`HardwareInfo`], given a [`slice`] containing
+[`postcard`]-serialized `u8`s preceded by a magic constant header.
+
+Unlike [`from_bytes_magic`], the header must be at the start of the [`slice`],
+since a [`HardwareInfo`] is expected at a fixed location. Any bytes following
+the [`HardwareInfo`], such as erased flash, are ignored.
+
+# Errors
+Returns [`postcard::Error::DeserializeBadEncoding`] if the [`slice`] does not
+start with the header (e.g. the board was never provisioned), or any other
+[`postcard::Error`] if deserializing fails.
+*/
+pub fn from_bytes_hardware(s: &[u8]) -> Result<HardwareInfo<'_>> {
+    let magic = de::Magic::try_with_header(de_flavors::Slice::new(s), &HARDWARE_MAGIC)?;
+    HardwareInfo::deserialize(&mut Deserializer::from_flavor(magic))
+}
+
+/** Deserialize [`HardwareInfo`] from a sequential source, such as AVR EEPROM,
+using `buf` to hold borrowed strings.
+
+This function is analogous to [`from_seq_magic`], and has the same header
+requirements as [`from_bytes_hardware`].
+
+# Errors
+See [`from_bytes_hardware`].
+*/
+pub fn from_seq_hardware<'buf, R, S>(src: R, buf: S) -> Result<HardwareInfo<'buf>>
+where
+    Seq<R, S>: de_flavors::Flavor<'buf>,
+{
+    let magic = de::Magic::try_with_header(Seq::new(src, buf), &HARDWARE_MAGIC)?;
+    HardwareInfo::deserialize(&mut Deserializer::from_flavor(magic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postcard::Error;
+
+    fn board() -> HardwareInfo<'static> {
+        HardwareInfo {
+            board: Some("pim-devkit".into()),
+            revision: Some("B2".into()),
+            serial: Some("000123".into()),
+            address: Some(HardwareAddress::Mac([0x02, 0, 0, 0x12, 0x34, 0x56])),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_trip_slice() {
+        let hw = board();
+
+        let mut buf = [0xff; 64];
+        let len = to_slice_hardware(&hw, &mut buf).unwrap().len();
+        assert_eq!(&buf[..4], b"PIB\x80");
+
+        // Trailing erased flash is ignored.
+        let de = from_bytes_hardware(&buf).unwrap();
+        assert_eq!(de, hw);
+        assert!(len < buf.len());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn round_trip_vec() {
+        let hw = board();
+
+        let ser = to_allocvec_hardware(&hw).unwrap();
+        assert_eq!(from_bytes_hardware(&ser).unwrap(), hw);
+    }
+
+    #[test]
+    fn round_trip_seq() {
+        let hw = board();
+
+        let mut ser = [0xff; 64];
+        to_slice_hardware(&hw, &mut ser).unwrap();
+
+        let mut buf = [0; 32];
+        let src = ser.iter().map(|&b| Ok::<_, SequentialReadError>(b));
+        assert_eq!(from_seq_hardware(src, &mut buf[..]).unwrap(), hw);
+
+        let src = [0xff; 16].into_iter().map(Ok::<_, SequentialReadError>);
+        assert_eq!(
+            from_seq_hardware(src, &mut buf[..]),
+            Err(Error::DeserializeBadEncoding)
+        );
+    }
+
+    #[test]
+    fn unprovisioned() {
+        assert_eq!(
+            from_bytes_hardware(&[0xff; 16]),
+            Err(Error::DeserializeBadEncoding)
+        );
+        assert_eq!(
+            to_slice_hardware(&board(), &mut [0; 2]),
+            Err(Error::SerializeBufferFull)
+        );
+    }
+}
@@ -0,0 +1,250 @@
+/*! Module implementing an optional image hash block following a serialized
+[`InfoMem`].
+
+The image hash is a SHA-256 digest of the application image that an
+[`InfoMem`] describes. Since the digest can only be computed after linking,
+the hash block is first written as a placeholder (all zeros), and then
+patched in place by a post-link step in [`postcard_infomem_host`](../postcard_infomem_host/index.html).
+
+A hashed [`InfoMem`] is laid out as follows:
+
+1. The serialized [`InfoMem`], including its [magic header](MAGIC).
+2. An optional [signature block](SIGNATURE_MAGIC).
+3. The [hash magic header](HASH_MAGIC).
+4. A [`HASH_LEN`]-byte SHA-256 digest.
+
+The digest covers the loadable contents of the application image in order of
+load address, with the digest itself (item 4) replaced by zeros. With the
+`sha256` feature, [`verify_image_hash`] checks the digest at runtime, e.g. from
+a bootloader; this is compatible with `no_std`.
+*/
+
+use crate::*;
+
+#[cfg(feature = "sha256")]
+use sha2::{Digest, Sha256};
+
+/** Magic constant header prepended to the image hash following a serialized
+[`InfoMem`]. */
+pub const HASH_MAGIC: [u8; 4] = [b'P', b'I', b'H', 0x80];
+
+/// Length of a SHA-256 digest in bytes.
+pub const HASH_LEN: usize = 32;
+
+/// Length of an image hash block, including its magic header.
+pub const HASH_BLOCK_LEN: usize = HASH_MAGIC.len() + HASH_LEN;
+
+/** Placeholder image hash block, to be appended to a serialized [`InfoMem`]
+(and its signature block, if any) before linking. */
+pub const HASH_PLACEHOLDER: [u8; HASH_BLOCK_LEN] = {
+    let mut block = [0; HASH_BLOCK_LEN];
+    let mut i = 0;
+
+    while i < HASH_MAGIC.len() {
+        block[i] = HASH_MAGIC[i];
+        i += 1;
+    }
+
+    block
+};
+
+/** Find the image hash following a serialized [`InfoMem`] preceded by a magic
+constant header, skipping over the signature block, if any.
+
+Returns the offset of the digest from the start of `s`, and the digest itself.
+The digest is all zeros if it has not been patched yet.
+
+# Errors
+Returns a [`postcard::Error`] if the [`slice`] does not contain a serialized
+[`InfoMem`]. Returns `Ok(None)` if the [`InfoMem`] has no hash block.
+*/
+pub fn find_image_hash(s: &[u8]) -> postcard::Result<Option<(usize, &[u8; HASH_LEN])>> {
+    let signed = split_signature(s)?;
+
+    let hash = signed
+        .rest
+        .strip_prefix(&HASH_MAGIC)
+        .and_then(|r| r.get(..HASH_LEN))
+        .and_then(|r| r.try_into().ok());
+
+    Ok(hash.map(|h| (s.len() - signed.rest.len() + HASH_MAGIC.len(), h)))
+}
+
+#[cfg(feature = "sha256")]
+/// Error returned when verifying the image hash of an [`InfoMem`] fails.
+#[derive(Debug, PartialEq)]
+pub enum HashError {
+    /// The [`InfoMem`] could not be deserialized.
+    Decode(postcard::Error),
+    /// No image hash block follows the [`InfoMem`].
+    Missing,
+    /// The image hash does not match the image.
+    Mismatch,
+}
+
+#[cfg(feature = "sha256")]
+impl core::fmt::Display for HashError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HashError::Decode(e) => write!(f, "could not deserialize InfoMem: {}", e),
+            HashError::Missing => write!(f, "InfoMem has no image hash"),
+            HashError::Mismatch => write!(f, "image hash does not match"),
+        }
+    }
+}
+
+#[cfg(all(feature = "sha256", feature = "std"))]
+impl std::error::Error for HashError {}
+
+#[cfg(feature = "sha256")]
+/** Compute the SHA-256 digest of an application image, given its loadable
+`segments` as `(load address, contents)` pairs in order of load address.
+
+The [`HASH_LEN`] bytes at load address `field_addr` are hashed as zeros
+wherever they overlap one of the `segments`, so that the digest can be stored
+inside the image itself. */
+pub fn image_hash<'a, I>(segments: I, field_addr: u64) -> [u8; HASH_LEN]
+where
+    I: IntoIterator<Item = (u64, &'a [u8])>,
+{
+    let field_end = field_addr.saturating_add(HASH_LEN as u64);
+    let mut hasher = Sha256::new();
+
+    for (addr, seg) in segments {
+        let seg_end = addr.saturating_add(seg.len() as u64);
+        let start = addr.max(field_addr);
+        let end = seg_end.min(field_end);
+
+        if start < end {
+            // Both ends lie within the segment, so these offsets fit its length.
+            let start = (start - addr) as usize;
+            let end = (end - addr) as usize;
+
+            hasher.update(&seg[..start]);
+            hasher.update(&[0; HASH_LEN][..end - start]);
+            hasher.update(&seg[end..]);
+        } else {
+            hasher.update(seg);
+        }
+    }
+
+    hasher.finalize().into()
+}
+
+#[cfg(feature = "sha256")]
+/** Verify the image hash of a serialized [`InfoMem`] against the application
+image it describes.
+
+`s` is the serialized [`InfoMem`] as it resides in the image (e.g. from
+`linker_section!()` or `generated_module_name::get()`), and `addr` is its load
+address. `segments` are the loadable contents of the image as
+`(load address, contents)` pairs in order of load address, exactly as the host
+hashed them; see [`image_hash`]. On a device that executes in place,
+`s.as_ptr() as u64` is the load address of `s`. Both must be in the same
+address space, so this function does not support information memory in AVR
+EEPROM.
+
+# Errors
+* [`HashError::Missing`]: Returned if the [`InfoMem`] is not followed by an
+  image hash block.
+* [`HashError::Mismatch`]: Returned if the digest of the image does not match.
+* [`HashError::Decode`]: Returned if deserializing fails.
+*/
+pub fn verify_image_hash<'a, I>(s: &[u8], addr: u64, segments: I) -> Result<(), HashError>
+where
+    I: IntoIterator<Item = (u64, &'a [u8])>,
+{
+    let (offset, expected) = find_image_hash(s)
+        .map_err(HashError::Decode)?
+        .ok_or(HashError::Missing)?;
+
+    if image_hash(segments, addr.saturating_add(offset as u64)) == *expected {
+        Ok(())
+    } else {
+        Err(HashError::Mismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::vec::Vec;
+
+    fn record() -> Vec<u8> {
+        to_allocvec_magic(&InfoMem::<&[u8]>::default()).unwrap()
+    }
+
+    #[test]
+    fn find_placeholder() {
+        let mut buf = record();
+        let len = buf.len();
+        buf.extend(HASH_PLACEHOLDER);
+
+        let (offset, hash) = find_image_hash(&buf).unwrap().unwrap();
+        assert_eq!(offset, len + HASH_MAGIC.len());
+        assert_eq!(hash, &[0; HASH_LEN]);
+        assert_eq!(find_image_hash(&record()).unwrap(), None);
+    }
+
+    #[test]
+    fn find_after_signature() {
+        let mut buf = record();
+        buf.extend(SIGNATURE_MAGIC);
+        buf.extend([0xa5; SIGNATURE_LEN]);
+        let len = buf.len();
+        buf.extend(HASH_PLACEHOLDER);
+
+        let (offset, _) = find_image_hash(&buf).unwrap().unwrap();
+        assert_eq!(offset, len + HASH_MAGIC.len());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn verify_in_image() {
+        // A fake image with code, followed by the InfoMem in the same segment.
+        let mut image = std::vec![0x5a; 64];
+        let start = image.len();
+        image.extend(record());
+        image.extend(HASH_PLACEHOLDER);
+        let end = image.len();
+        let (offset, _) = find_image_hash(&image[start..end]).unwrap().unwrap();
+
+        // Patch the digest, as the host would.
+        let field = start + offset;
+        let mut hasher = Sha256::new();
+        hasher.update(&image[..field]);
+        hasher.update([0; HASH_LEN]);
+        hasher.update(&image[field + HASH_LEN..]);
+        let digest: [u8; HASH_LEN] = hasher.finalize().into();
+        image[field..field + HASH_LEN].copy_from_slice(&digest);
+
+        // Load the fake image at an arbitrary address.
+        const BASE: u64 = 0x1000_0000;
+        let addr = BASE + start as u64;
+        let info = &image[start..end];
+        assert_eq!(verify_image_hash(info, addr, [(BASE, &image[..])]), Ok(()));
+
+        // The digest is also correct if the image is split into segments.
+        let (code, rest) = image.split_at(start);
+        assert_eq!(
+            verify_image_hash(info, addr, [(BASE, code), (addr, rest)]),
+            Ok(())
+        );
+        assert_eq!(
+            verify_image_hash(info, addr, [(BASE, code)]),
+            Err(HashError::Mismatch)
+        );
+
+        // The digest is only zeroed at the field's load address.
+        assert_eq!(
+            verify_image_hash(info, addr + 1, [(BASE, &image[..])]),
+            Err(HashError::Mismatch)
+        );
+        assert_eq!(
+            verify_image_hash(&record(), addr, [(BASE, &image[..])]),
+            Err(HashError::Missing)
+        );
+    }
+}
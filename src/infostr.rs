@@ -7,6 +7,7 @@
 //! Inspired by @whitequark's `managed` crate.
 
 use core::fmt::Debug;
+use serde::de::{self, Visitor};
 use serde::{Deserialize, Serialize};
 
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
@@ -48,6 +49,11 @@ impl<'a> InfoStr<'a> {
             InfoStr::Owned(s) => s.as_str(),
         }
     }
+
+    /// Borrow the InfoStr, whether it is owned or not
+    pub fn as_borrowed(&self) -> InfoStr<'_> {
+        InfoStr::Borrowed(self.as_str())
+    }
 }
 
 // Optional impls
@@ -109,7 +115,42 @@ impl<'a, 'de: 'a> Deserialize<'de> for InfoStr<'a> {
     where
         D: serde::Deserializer<'de>,
     {
-        let stir = <&'de str as Deserialize<'de>>::deserialize(deserializer)?;
-        Ok(InfoStr::Borrowed(stir))
+        deserializer.deserialize_str(InfoStrVisitor)
+    }
+}
+
+/** Visitor which borrows from the deserializer's input where possible (e.g.
+always with [`postcard`]), and otherwise creates an owned `String`, if
+available. */
+struct InfoStrVisitor;
+
+impl<'de> Visitor<'de> for InfoStrVisitor {
+    type Value = InfoStr<'de>;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "a string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(InfoStr::Borrowed(v))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(InfoStr::Owned(v.to_string()))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(InfoStr::Owned(v))
     }
 }
@@ -9,6 +9,9 @@ help with this.
 
 User-defined data is supported in the form of a generic parameter, whose data
 is placed into the [`user`](InfoMem::user) field of the main [`InfoMem`] struct.
+The data can either be an opaque byte blob, or a user-defined `struct` wrapped
+in [`Typed`]. Several independent blobs can share the payload as a list of
+tagged [`Entry`]s.
 */
 
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -18,6 +21,15 @@ use core::fmt::Debug;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
+mod entries;
+pub use entries::{find_entry, find_entry_seq, Entry};
+
+mod hardware;
+pub use hardware::*;
+
+mod hash;
+pub use hash::*;
+
 mod infostr;
 pub use infostr::InfoStr;
 
@@ -25,11 +37,17 @@ mod magic;
 pub use magic::*;
 
 mod seq;
-pub use seq::{from_seq, take_from_seq, from_seq_magic, from_seq_magic_deferred, SequentialReadError};
+pub use seq::{from_seq, take_from_seq, from_seq_magic, from_seq_magic_deferred, Deferred, SequentialReadError};
 
 mod shim;
 pub use shim::*;
 
+mod sig;
+pub use sig::*;
+
+mod typed;
+pub use typed::Typed;
+
 pub mod de {
     pub use super::magic::de::Magic;
     // Everything under seq is for deserialization.
@@ -75,10 +93,13 @@ where
     #[serde(borrow)]
     /// Information about the `rustc` compiler used to originally create this `struct`.
     pub rustc: RustcInfo<'a>,
-    /** User-specific information to be included "as-is" (either `&[u8]`, `&mut [u8]`, or [`Vec<u8>`]).
+    /** User-specific information to be included "as-is" (either `&[u8]`, `&mut [u8]`, or [`Vec<u8>`]),
+    or a user-defined `struct` wrapped in [`Typed`].
 
-    It is up to the user to ensure that the data contained in this field is
-    parsed or deserialized by external means. */
+    For byte blobs, it is up to the user to ensure that the data contained in
+    this field is parsed or deserialized by external means, e.g. with
+    [`InfoMem::user_typed`]. [`Typed`] payloads are (de)serialized along with
+    the rest of the [`InfoMem`]. Both are identical on the wire. */
     pub user: Option<T>,
 }
 
@@ -100,6 +121,9 @@ pub(crate) mod sealed {
 
     // Deferred processing of user-payload.
     impl Sealed for super::seq::Deferred {}
+
+    // User-defined payload, serialized as a byte blob.
+    impl<U> Sealed for super::typed::Typed<U> {}
 }
 
 impl<'a, T> Default for InfoMem<'a, T>
@@ -128,6 +152,23 @@ where
     }
 }
 
+impl<'a, T> InfoMem<'a, T>
+where
+    T: sealed::Sealed + AsRef<[u8]>,
+{
+    /** Borrow an [`InfoMem`] with a byte blob [`user`](InfoMem::user) payload
+    as one with a `&[u8]` payload, e.g. to pass an `InfoMem<Vec<u8>>` to
+    functions expecting an [`InfoMem`]. */
+    pub fn as_borrowed(&self) -> InfoMem<'_> {
+        InfoMem {
+            version: self.version.as_borrowed(),
+            app: self.app.as_borrowed(),
+            rustc: self.rustc.as_borrowed(),
+            user: self.user.as_ref().map(AsRef::as_ref),
+        }
+    }
+}
+
 /** Information about the current crate being compiled.
 
 This `struct` is likely to be filled in using e.g. [`generate_from_env`](../postcard_infomem_host/fn.generate_from_env.html)
@@ -148,10 +189,24 @@ pub struct AppInfo<'a> {
     #[serde(borrow)]
     /// Git commit of the source code of the current crate being compiled.
     pub git: Option<InfoStr<'a>>,
-    /// Build date of the current crate being compiled.
+    #[serde(default, with = "crate::shim::date")]
+    /** Build date of the current crate being compiled. In human-readable
+    formats, this is an [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) string. */
     pub build_date: Option<OffsetDateTime>,
 }
 
+impl<'a> AppInfo<'a> {
+    /// Borrow an [`AppInfo`] whose strings may be owned.
+    pub fn as_borrowed(&self) -> AppInfo<'_> {
+        AppInfo {
+            name: self.name.as_ref().map(InfoStr::as_borrowed),
+            version: self.version.as_ref().map(Semver::as_borrowed),
+            git: self.git.as_ref().map(InfoStr::as_borrowed),
+            build_date: self.build_date,
+        }
+    }
+}
+
 impl<'a> Default for AppInfo<'a> {
     fn default() -> Self {
         Self {
@@ -190,6 +245,19 @@ pub struct RustcInfo<'a> {
     pub host: Option<InfoStr<'a>>,
 }
 
+impl<'a> RustcInfo<'a> {
+    /// Borrow a [`RustcInfo`] whose strings may be owned.
+    pub fn as_borrowed(&self) -> RustcInfo<'_> {
+        RustcInfo {
+            version: self.version.as_ref().map(Semver::as_borrowed),
+            llvm_version: self.llvm_version.as_ref().map(Semver::as_borrowed),
+            channel: self.channel,
+            git: self.git.as_ref().map(InfoStr::as_borrowed),
+            host: self.host.as_ref().map(InfoStr::as_borrowed),
+        }
+    }
+}
+
 /// Create an empty [`RustcInfo`] with [`Option::None`]s, to be populated by external means.
 impl<'a> Default for RustcInfo<'a> {
     fn default() -> Self {
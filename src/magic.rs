@@ -2,7 +2,7 @@
 [flavor](postcard#flavors) flavor for prepending/removing a header.
 */
 
-pub use de::from_bytes_magic;
+pub use de::{from_bytes_magic, iter_from_bytes_magic, take_from_bytes_magic, IterMagic};
 pub use ser::to_slice_magic;
 
 #[cfg(feature = "alloc")]
@@ -11,6 +11,11 @@ pub use ser::to_allocvec_magic;
 #[cfg(feature = "std")]
 pub use ser::to_allocvec_magic as to_stdvec_magic;
 
+/** Magic constant header prepended to a serialized [`InfoMem`](crate::InfoMem).
+
+End with 0x80 to avoid the temptation to serialize as UTF-8 string. */
+pub const MAGIC: [u8; 4] = [b'P', b'I', b'M', 0x80];
+
 pub(crate) mod ser {
     /*! Serialization methods and traits for serializing [`InfoMem`] to the
     [`postcard`] wire format.
@@ -84,9 +89,16 @@ pub(crate) mod ser {
         Returns a [`postcard::Error`] from the underlying flavor `B`, if
         adding a header fails.
         */
-        pub fn try_new(mut flav: B) -> Result<Self> {
-            // End with 0x80 to avoid the temptation to serialize as UTF-8 string.
-            flav.try_extend(&[b'P', b'I', b'M', 0x80])?;
+        pub fn try_new(flav: B) -> Result<Self> {
+            Self::try_with_header(flav, &MAGIC)
+        }
+
+        /**
+        Like [`Magic::try_new`], but add `header` instead of the
+        [`InfoMem`] magic header, e.g. [`HARDWARE_MAGIC`].
+        */
+        pub(crate) fn try_with_header(mut flav: B, header: &[u8]) -> Result<Self> {
+            flav.try_extend(header)?;
             Ok(Self(flav))
         }
     }
@@ -137,6 +149,73 @@ pub(crate) mod de {
         InfoMem::deserialize(&mut de_magic)
     }
 
+    /** Deserialize [`InfoMem`] into a `T`, given a [`slice`] containing
+    [`postcard`]-serialized `u8`s preceded by a magic constant header. Return
+    the deserialized [`InfoMem`] and the remainder of the [`slice`] after it.
+
+    This function is analogous to [`postcard::take_from_bytes`]. */
+    pub fn take_from_bytes_magic<'de, T>(s: &'de [u8]) -> Result<(InfoMem<'de, T>, &'de [u8])>
+    where
+        T: sealed::Sealed + Deserialize<'de>,
+    {
+        let mut de_magic = Deserializer::from_flavor(de::Magic::try_new(Slice::new(s))?);
+        let im = InfoMem::deserialize(&mut de_magic)?;
+        let rest = de_magic.finalize()?;
+
+        Ok((im, rest))
+    }
+
+    /** Create an [`Iterator`] over every [`InfoMem`] `struct` preceded by a
+    magic constant header inside a [`slice`].
+
+    This is intended to be used when multiple [`InfoMem`] `struct`s have been
+    placed in the same memory region or linker section, e.g. one each for a
+    bootloader and an application. See [`IterMagic`] for details. */
+    pub fn iter_from_bytes_magic<'de, T>(s: &'de [u8]) -> IterMagic<'de, T>
+    where
+        T: sealed::Sealed + Deserialize<'de>,
+    {
+        IterMagic {
+            rest: s,
+            _phantom: PhantomData,
+        }
+    }
+
+    /** An [`Iterator`] over every [`InfoMem`] `struct` preceded by a magic
+    constant header inside a [`slice`].
+
+    Bytes between [`InfoMem`]s, such as padding, are skipped. If a magic
+    header is found, but the data following it does not deserialize to an
+    [`InfoMem`], the header is assumed to be a false positive and the search
+    resumes immediately after it. */
+    pub struct IterMagic<'de, T> {
+        /// Portion of the [`slice`] that has not been searched yet.
+        rest: &'de [u8],
+        /// Marker type representing the user payload type.
+        _phantom: PhantomData<T>,
+    }
+
+    impl<'de, T> Iterator for IterMagic<'de, T>
+    where
+        T: sealed::Sealed + Deserialize<'de>,
+    {
+        type Item = InfoMem<'de, T>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let start = self.rest.windows(MAGIC.len()).position(|w| w == MAGIC)?;
+
+                match take_from_bytes_magic(&self.rest[start..]) {
+                    Ok((im, rest)) => {
+                        self.rest = rest;
+                        return Some(im);
+                    }
+                    Err(_) => self.rest = &self.rest[start + MAGIC.len()..],
+                }
+            }
+        }
+    }
+
     #[derive(PartialEq)]
     /** A state machine [`enum`] for decoding the magic header. */
     enum State {
@@ -209,6 +288,32 @@ pub(crate) mod de {
                 _phantom: PhantomData,
             })
         }
+
+        /**
+        Like [`Magic::try_new`], but remove `header` instead of the
+        [`InfoMem`] magic header, e.g. [`HARDWARE_MAGIC`].
+
+        Unlike [`Magic::try_new`], `header` must be the first bytes returned
+        by `flav`; no search is performed.
+
+        # Errors
+
+        Returns [`postcard::Error::DeserializeBadEncoding`] if `flav` does not
+        start with `header`, or a [`postcard::Error`] from the underlying
+        flavor `B`.
+        */
+        pub(crate) fn try_with_header(mut flav: B, header: &[u8]) -> Result<Self> {
+            for &expected in header {
+                if flav.pop()? != expected {
+                    return Err(postcard::Error::DeserializeBadEncoding);
+                }
+            }
+
+            Ok(Self {
+                flav,
+                _phantom: PhantomData,
+            })
+        }
     }
 
     impl<'de, B> Flavor<'de> for Magic<'de, B>
@@ -234,12 +339,12 @@ pub(crate) mod de {
 
 #[cfg(test)]
 mod tests {
-    use crate::from_bytes_magic;
-    use crate::{to_stdvec_magic, InfoMem};
+    use crate::{from_bytes_magic, iter_from_bytes_magic, take_from_bytes_magic};
+    use crate::{to_stdvec_magic, InfoMem, InfoStr};
     use postcard::Error;
 
     extern crate std;
-    use std::{print, vec};
+    use std::{print, vec, vec::Vec};
 
     #[test]
     fn test_magic_ser() {
@@ -295,6 +400,42 @@ mod tests {
         assert_eq!(im, de);
     }
 
+    #[test]
+    fn test_magic_take_multiple() {
+        let mut first: InfoMem = InfoMem::default();
+        first.app.name = Some(InfoStr::Borrowed("bootloader"));
+        let mut second: InfoMem = InfoMem::default();
+        second.app.name = Some(InfoStr::Borrowed("application"));
+
+        let mut all_data = to_stdvec_magic(&first).unwrap();
+        all_data.extend(to_stdvec_magic(&second).unwrap());
+
+        let (de_first, rest) = take_from_bytes_magic(&all_data).unwrap();
+        let (de_second, rest) = take_from_bytes_magic(rest).unwrap();
+
+        assert_eq!(first, de_first);
+        assert_eq!(second, de_second);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_magic_iter_with_padding_and_false_header() {
+        let mut first: InfoMem = InfoMem::default();
+        first.app.name = Some(InfoStr::Borrowed("bootloader"));
+        let mut second: InfoMem = InfoMem::default();
+        second.app.name = Some(InfoStr::Borrowed("application"));
+
+        let mut all_data = vec![0xff, b'P', b'I', b'M', 0x80, 0xff];
+        all_data.extend(to_stdvec_magic(&first).unwrap());
+        all_data.extend([0xff; 7]);
+        all_data.extend(to_stdvec_magic(&second).unwrap());
+        all_data.extend([0xff; 3]);
+
+        let de: Vec<InfoMem> = iter_from_bytes_magic(&all_data).collect();
+
+        assert_eq!(de, vec![first, second]);
+    }
+
     #[test]
     fn test_magic_ok_header_bad_data() {
         let bad_data = [b'P', b'I', b'M', 0x80, 0x00, 0x01, 0x00, 0xff];
@@ -1,3 +1,4 @@
+use core::iter::Take;
 use core::result::Result as CoreResult;
 
 use super::*;
@@ -10,10 +11,39 @@ use serde::{self, Deserialize};
 #[derive(Debug, Clone, Copy)]
 pub struct SequentialReadError;
 
+/** User payload whose contents were not read by [`from_seq_magic_deferred`].
+
+Only the length of the payload is deserialized. The payload itself is the
+first [`len`](Deferred::len) bytes of the returned remainder. */
 #[derive(Debug, Clone, Deserialize)]
 #[repr(transparent)]
 pub struct Deferred(usize);
 
+impl Deferred {
+    /// Length of the user payload in bytes.
+    pub fn len(&self) -> usize {
+        self.0
+    }
+
+    /// Returns `true` if the user payload has a length of zero bytes.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /** Bound the remainder returned by [`from_seq_magic_deferred`] to just
+    the user payload, e.g. to stream the payload elsewhere without a buffer
+    large enough to hold it.
+
+    Pass `rest.by_ref()` to continue reading past the end of the payload
+    afterwards. */
+    pub fn take<R>(&self, rest: R) -> Take<R>
+    where
+        R: Iterator,
+    {
+        rest.take(self.0)
+    }
+}
+
 pub struct Seq<R, S> {
     src: R,
     buf: S,
@@ -188,6 +218,32 @@ mod tests {
         assert_eq!(user_data, b"test data");
     }
 
+    #[test]
+    fn test_deser_user_payload_deferred_bounded() {
+        let im: InfoMem = InfoMem {
+            user: Some(b"calibration table"),
+            ..Default::default()
+        };
+
+        let mut buf = [0; 16];
+        let mut ser = to_stdvec_magic(&im).unwrap();
+        ser.extend(b"trailer");
+
+        let (im_de, mut rest) = from_seq_magic_deferred(seq_vec(ser), &mut buf).unwrap();
+        let user = im_de.user.unwrap();
+        assert_eq!(user.len(), 17);
+        assert!(!user.is_empty());
+
+        let user_data = user
+            .take(rest.by_ref())
+            .collect::<CoreResult<Vec<u8>, SequentialReadError>>()
+            .unwrap();
+        assert_eq!(user_data, b"calibration table");
+
+        let trailer = rest.collect::<CoreResult<Vec<u8>, SequentialReadError>>().unwrap();
+        assert_eq!(trailer, b"trailer");
+    }
+
     #[test]
     fn test_deser_user_payload_serialized_deferred() {
         let mut im: InfoMem<Vec<u8>> = InfoMem::default();
@@ -207,4 +263,17 @@ mod tests {
         assert_eq!(user_data, (0xff, b"test data".as_ref()));
         assert_eq!(&user_buf[0..9], b"test data");
     }
+
+    #[test]
+    fn test_deser_user_payload_typed() {
+        let mut im: InfoMem<Typed<(u8, &[u8])>> = InfoMem::default();
+        im.app.name = Some(InfoStr::Borrowed("test_deser_user_payload_typed"));
+        im.user = Some(Typed((0xff, b"test data".as_ref())));
+
+        let mut buf = [0; 64];
+        let ser = to_stdvec_magic(&im).unwrap();
+
+        let im_de: InfoMem<Typed<(u8, &[u8])>> = from_seq_magic(seq_vec(ser), &mut buf).unwrap();
+        assert_eq!(im, im_de);
+    }
 }
@@ -8,7 +8,9 @@ use std::error::Error as StdError;
 
 #[cfg(feature = "alloc")]
 use semver;
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 extern crate alloc;
@@ -18,13 +20,15 @@ use alloc::string::ToString;
 #[cfg(feature = "std")]
 use rustc_version;
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /** [`enum`] representing the [release channel](https://doc.rust-lang.org/book/appendix-07-nightly-rust.html)
 of the `rustc` compiler.
 
 This enum is created from a [`rustc_version::Channel`] using its [`From`]
 implementation, and exists mainly to aid in [deriving](https://serde.rs/remote-derive.html)
-the [`Serialize`] and [`Deserialize`] traits for [`RustcInfo`].
+the [`Serialize`] and [`Deserialize`] traits for [`RustcInfo`]. In
+human-readable formats, variants are lowercase names, e.g. `"stable"`.
 */
 pub enum Channel {
     /// Development release channel
@@ -63,17 +67,79 @@ impl From<Channel> for rustc_version::Channel {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/** A [semantic version](https://semver.org/).
+
+With the `std` feature, human-readable formats (e.g. JSON) represent a
+[`Semver`] as a string, e.g. `"1.2.3-pre+build"`. This is the same feature
+that writes dates as RFC 3339 strings. */
+#[derive(Debug, PartialEq)]
 pub struct Semver<'a> {
     pub major: usize,
     pub minor: usize,
     pub patch: usize,
-    #[serde(borrow)]
     pub pre: Option<InfoStr<'a>>,
-    #[serde(borrow)]
     pub build: Option<InfoStr<'a>>,
 }
 
+/// Wire format of [`Semver`], for non-human-readable formats like [`postcard`].
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Semver", rename = "Semver")]
+struct SemverDef<'a> {
+    major: usize,
+    minor: usize,
+    patch: usize,
+    #[serde(borrow)]
+    pre: Option<InfoStr<'a>>,
+    #[serde(borrow)]
+    build: Option<InfoStr<'a>>,
+}
+
+impl<'a> fmt::Display for Semver<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre.as_str())?;
+        }
+
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build.as_str())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Serialize for Semver<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[cfg(feature = "std")]
+        if serializer.is_human_readable() {
+            return serializer.collect_str(self);
+        }
+
+        SemverDef::serialize(self, serializer)
+    }
+}
+
+impl<'a, 'de: 'a> Deserialize<'de> for Semver<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[cfg(feature = "std")]
+        if deserializer.is_human_readable() {
+            let stir = InfoStr::deserialize(deserializer)?;
+            let version = semver::Version::parse(stir.as_str()).map_err(de::Error::custom)?;
+            return version.try_into().map_err(de::Error::custom);
+        }
+
+        SemverDef::deserialize(deserializer)
+    }
+}
+
 impl<'a> Semver<'a> {
     pub(super) const fn this_version() -> Self {
         Self {
@@ -84,6 +150,17 @@ impl<'a> Semver<'a> {
             build: None,
         }
     }
+
+    /// Borrow a [`Semver`] whose strings may be owned.
+    pub fn as_borrowed(&self) -> Semver<'_> {
+        Semver {
+            major: self.major,
+            minor: self.minor,
+            patch: self.patch,
+            pre: self.pre.as_ref().map(InfoStr::as_borrowed),
+            build: self.build.as_ref().map(InfoStr::as_borrowed),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -98,6 +175,54 @@ impl fmt::Display for TryFromVersionError {
 #[cfg(feature = "std")]
 impl StdError for TryFromVersionError {}
 
+/** (De)serialize an optional date as an [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339)
+string in human-readable formats (with the `std` feature), and as [`time`]'s
+default representation otherwise. Intended for `#[serde(with = "...")]`.
+
+This is public so that `postcard-infomem-host` can read dates the same way,
+and is not part of the stable API. */
+#[doc(hidden)]
+pub mod date {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use time::OffsetDateTime;
+
+    #[cfg(feature = "std")]
+    use serde::{de, ser};
+    #[cfg(feature = "std")]
+    use time::format_description::well_known::Rfc3339;
+
+    pub fn serialize<S>(date: &Option<OffsetDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[cfg(feature = "std")]
+        if serializer.is_human_readable() {
+            return date
+                .map(|d| d.format(&Rfc3339))
+                .transpose()
+                .map_err(ser::Error::custom)?
+                .serialize(serializer);
+        }
+
+        date.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<OffsetDateTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[cfg(feature = "std")]
+        if deserializer.is_human_readable() {
+            return Option::<String>::deserialize(deserializer)?
+                .map(|d| OffsetDateTime::parse(&d, &Rfc3339))
+                .transpose()
+                .map_err(de::Error::custom);
+        }
+
+        Option::<OffsetDateTime>::deserialize(deserializer)
+    }
+}
+
 #[cfg(feature = "alloc")]
 impl<'a> TryFrom<semver::Version> for Semver<'a> {
     type Error = TryFromVersionError;
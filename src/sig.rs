@@ -0,0 +1,233 @@
+/*! Module implementing an optional signature block following a serialized
+[`InfoMem`].
+
+A signed [`InfoMem`] is laid out as follows:
+
+1. The serialized [`InfoMem`], including its [magic header](MAGIC).
+2. The [signature magic header](SIGNATURE_MAGIC).
+3. A [`SIGNATURE_LEN`]-byte Ed25519 signature over item 1.
+
+Since the signature block comes after the serialized [`InfoMem`], readers that
+do not know about signatures (e.g. [`from_bytes_magic`]) ignore it. Signatures
+are created by [`postcard_infomem_host`](../postcard_infomem_host/index.html),
+and verified by [`from_bytes_magic_signed`] with the `ed25519` feature, which
+is compatible with `no_std`.
+*/
+
+use crate::*;
+
+#[cfg(feature = "ed25519")]
+use ed25519_dalek::{Signature, VerifyingKey};
+#[cfg(feature = "ed25519")]
+use serde::Deserialize;
+
+/** Magic constant header prepended to the signature following a serialized
+[`InfoMem`]. */
+pub const SIGNATURE_MAGIC: [u8; 4] = [b'P', b'I', b'S', 0x80];
+
+/// Length of an Ed25519 signature in bytes.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Length of a signature block, including its magic header.
+pub const SIGNATURE_BLOCK_LEN: usize = SIGNATURE_MAGIC.len() + SIGNATURE_LEN;
+
+/// A serialized [`InfoMem`] split into its parts by [`split_signature`].
+#[derive(Debug, PartialEq)]
+pub struct Signed<'a> {
+    /// The serialized [`InfoMem`], including its magic header. This is the
+    /// data covered by the signature.
+    pub record: &'a [u8],
+    /// The signature, if a signature block follows `record`.
+    pub signature: Option<&'a [u8; SIGNATURE_LEN]>,
+    /// Remainder of the [`slice`] after the record and signature block.
+    pub rest: &'a [u8],
+}
+
+/** Split a [`slice`] starting with a serialized [`InfoMem`] preceded by a
+magic constant header into the record, its signature (if any), and the
+remainder of the [`slice`].
+
+# Errors
+Returns a [`postcard::Error`] if the [`slice`] does not contain a serialized
+[`InfoMem`]. A missing signature block is _not_ an error.
+*/
+pub fn split_signature(s: &[u8]) -> postcard::Result<Signed<'_>> {
+    let (_, rest) = take_from_bytes_magic::<&[u8]>(s)?;
+    let record = &s[..s.len() - rest.len()];
+
+    let signature = rest
+        .strip_prefix(&SIGNATURE_MAGIC)
+        .and_then(|r| r.get(..SIGNATURE_LEN))
+        .and_then(|r| r.try_into().ok());
+
+    let rest = match signature {
+        Some(_) => &rest[SIGNATURE_BLOCK_LEN..],
+        None => rest,
+    };
+
+    Ok(Signed {
+        record,
+        signature,
+        rest,
+    })
+}
+
+#[cfg(feature = "ed25519")]
+/// Error returned when verifying a signed [`InfoMem`] fails.
+#[derive(Debug, PartialEq)]
+pub enum SignatureError {
+    /// The [`InfoMem`] could not be deserialized.
+    Decode(postcard::Error),
+    /// No signature block follows the [`InfoMem`].
+    Missing,
+    /// The signature does not match the [`InfoMem`] and public key.
+    Bad,
+    /// The public key is not a valid Ed25519 public key.
+    InvalidKey,
+}
+
+#[cfg(feature = "ed25519")]
+impl core::fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SignatureError::Decode(e) => write!(f, "could not deserialize InfoMem: {}", e),
+            SignatureError::Missing => write!(f, "InfoMem is not signed"),
+            SignatureError::Bad => write!(f, "InfoMem signature is invalid"),
+            SignatureError::InvalidKey => write!(f, "public key is invalid"),
+        }
+    }
+}
+
+#[cfg(all(feature = "ed25519", feature = "std"))]
+impl std::error::Error for SignatureError {}
+
+#[cfg(feature = "ed25519")]
+/** Deserialize a signed [`InfoMem`] into a `T`, given a [`slice`] containing
+[`postcard`]-serialized `u8`s preceded by a magic constant header, and
+followed by a signature block.
+
+The signature is checked against `public_key` (probably compiled into a
+bootloader) _before_ deserializing. This function is otherwise analogous to
+[`from_bytes_magic`].
+
+# Errors
+* [`SignatureError::Missing`]: Returned if the [`InfoMem`] is not followed by a
+  signature block.
+* [`SignatureError::Bad`]: Returned if the signature does not verify.
+* [`SignatureError::InvalidKey`]: Returned if `public_key` is malformed.
+* [`SignatureError::Decode`]: Returned if deserializing fails.
+*/
+pub fn from_bytes_magic_signed<'de, T>(
+    s: &'de [u8],
+    public_key: &[u8; 32],
+) -> Result<InfoMem<'de, T>, SignatureError>
+where
+    T: sealed::Sealed + Deserialize<'de>,
+{
+    let signed = split_signature(s).map_err(SignatureError::Decode)?;
+    let signature = signed.signature.ok_or(SignatureError::Missing)?;
+    let key = VerifyingKey::from_bytes(public_key).map_err(|_| SignatureError::InvalidKey)?;
+
+    key.verify_strict(signed.record, &Signature::from_bytes(signature))
+        .map_err(|_| SignatureError::Bad)?;
+
+    from_bytes_magic(signed.record).map_err(SignatureError::Decode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::vec::Vec;
+
+    fn record() -> Vec<u8> {
+        let mut im: InfoMem = InfoMem::default();
+        im.app.name = Some("signed".into());
+
+        to_allocvec_magic(&im).unwrap()
+    }
+
+    #[test]
+    fn split_unsigned() {
+        let mut buf = record();
+        let len = buf.len();
+        buf.extend([0xff; 8]);
+
+        let signed = split_signature(&buf).unwrap();
+        assert_eq!(signed.record, &buf[..len]);
+        assert_eq!(signed.signature, None);
+        assert_eq!(signed.rest, &[0xff; 8]);
+    }
+
+    #[test]
+    fn split_signed() {
+        let mut buf = record();
+        let len = buf.len();
+        buf.extend(SIGNATURE_MAGIC);
+        buf.extend([0xa5; SIGNATURE_LEN]);
+
+        let signed = split_signature(&buf).unwrap();
+        assert_eq!(signed.record, &buf[..len]);
+        assert_eq!(signed.signature, Some(&[0xa5; SIGNATURE_LEN]));
+        assert!(signed.rest.is_empty());
+    }
+
+    #[cfg(feature = "ed25519")]
+    mod ed25519 {
+        use super::*;
+        use ed25519_dalek::{Signer, SigningKey};
+
+        fn sign(buf: &mut Vec<u8>, key: &SigningKey) {
+            let sig = key.sign(buf).to_bytes();
+            buf.extend(SIGNATURE_MAGIC);
+            buf.extend(sig);
+        }
+
+        #[test]
+        fn verify_ok() {
+            let key = SigningKey::from_bytes(&[1; 32]);
+            let mut buf = record();
+            sign(&mut buf, &key);
+
+            let im: InfoMem =
+                from_bytes_magic_signed(&buf, key.verifying_key().as_bytes()).unwrap();
+            assert_eq!(im.app.name, Some("signed".into()));
+        }
+
+        #[test]
+        fn verify_missing() {
+            let key = SigningKey::from_bytes(&[1; 32]);
+            let buf = record();
+
+            assert_eq!(
+                from_bytes_magic_signed::<&[u8]>(&buf, key.verifying_key().as_bytes()),
+                Err(SignatureError::Missing)
+            );
+        }
+
+        #[test]
+        fn verify_bad() {
+            let key = SigningKey::from_bytes(&[1; 32]);
+            let other = SigningKey::from_bytes(&[2; 32]);
+            let mut buf = record();
+            sign(&mut buf, &other);
+
+            assert_eq!(
+                from_bytes_magic_signed::<&[u8]>(&buf, key.verifying_key().as_bytes()),
+                Err(SignatureError::Bad)
+            );
+
+            // Tamper with the record itself.
+            let mut buf = record();
+            sign(&mut buf, &key);
+            let name = buf.windows(6).position(|w| w == b"signed").unwrap();
+            buf[name] = b'S';
+
+            assert_eq!(
+                from_bytes_magic_signed::<&[u8]>(&buf, key.verifying_key().as_bytes()),
+                Err(SignatureError::Bad)
+            );
+        }
+    }
+}
@@ -0,0 +1,220 @@
+/*! Module implementing typed user-defined payloads for [`InfoMem`].
+
+A [`Typed`] payload is (de)serialized in a single pass along with the rest of
+an [`InfoMem`], without having to serialize the user's data into a byte blob
+first. On the [`postcard`] wire format, a [`Typed`] payload is
+indistinguishable from a byte blob containing the [`postcard`]-serialized user
+data. Therefore, readers which treat the user payload as opaque bytes
+(`&[u8]`, [`Deferred`], etc.) remain compatible.
+*/
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use postcard::ser_flavors::Flavor;
+use postcard::serialize_with_flavor;
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::{self, SerializeTuple, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/** Wrapper for a user-defined `struct` to be used as the [`user`](InfoMem::user)
+payload of an [`InfoMem`], e.g. `InfoMem<Typed<MyData>>`.
+
+With a human-readable format (e.g. JSON), the wrapped value is (de)serialized
+as-is. */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Typed<U>(pub U);
+
+/** [`postcard`] [flavor](postcard#flavors) which only counts the bytes
+serialized, to length-prefix a [`Typed`] payload without a buffer. */
+struct Count(usize);
+
+impl Flavor for Count {
+    type Output = usize;
+
+    fn try_push(&mut self, _data: u8) -> postcard::Result<()> {
+        self.0 += 1;
+        Ok(())
+    }
+
+    fn try_extend(&mut self, data: &[u8]) -> postcard::Result<()> {
+        self.0 += data.len();
+        Ok(())
+    }
+
+    fn finalize(self) -> postcard::Result<usize> {
+        Ok(self.0)
+    }
+}
+
+/// Encode `value` as a [`postcard`] varint, returning the number of bytes used.
+fn encode_varint(mut value: usize, buf: &mut [u8; 10]) -> usize {
+    let mut i = 0;
+
+    loop {
+        buf[i] = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            return i + 1;
+        }
+
+        buf[i] |= 0x80;
+        i += 1;
+    }
+}
+
+impl<U> Serialize for Typed<U>
+where
+    U: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            return self.0.serialize(serializer);
+        }
+
+        // Postcard bytes are a varint length followed by the raw bytes, while
+        // postcard tuples have no length prefix. Emit the length by hand, and
+        // then the payload itself, so no intermediate buffer is needed.
+        let len = serialize_with_flavor(&self.0, Count(0)).map_err(ser::Error::custom)?;
+        let mut varint = [0; 10];
+        let varint_len = encode_varint(len, &mut varint);
+
+        let mut tup = serializer.serialize_tuple(varint_len + 1)?;
+        for b in &varint[..varint_len] {
+            tup.serialize_element(b)?;
+        }
+        tup.serialize_element(&self.0)?;
+        tup.end()
+    }
+}
+
+impl<'de, U> Deserialize<'de> for Typed<U>
+where
+    U: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            return U::deserialize(deserializer).map(Typed);
+        }
+
+        struct TypedVisitor<U>(PhantomData<U>);
+
+        impl<'de, U> Visitor<'de> for TypedVisitor<U>
+        where
+            U: Deserialize<'de>,
+        {
+            type Value = Typed<U>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a postcard-serialized user payload")
+            }
+
+            fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                postcard::from_bytes(v).map(Typed).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(TypedVisitor(PhantomData))
+    }
+}
+
+impl<'a> InfoMem<'a, &'a [u8]> {
+    /** Deserialize the opaque [`user`](InfoMem::user) payload into a `U`,
+    as if the [`InfoMem`] had been deserialized with a [`Typed<U>`] payload.
+
+    # Errors
+    Returns a [`postcard::Error`] if deserializing the payload fails. A
+    missing payload is _not_ an error.
+    */
+    pub fn user_typed<U>(&self) -> postcard::Result<Option<U>>
+    where
+        U: Deserialize<'a>,
+    {
+        self.user.map(postcard::from_bytes).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postcard::{from_bytes, to_allocvec};
+
+    extern crate std;
+    use std::vec::Vec;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Board<'a> {
+        revision: u8,
+        serial: u32,
+        name: &'a str,
+    }
+
+    fn board() -> Board<'static> {
+        Board {
+            revision: 3,
+            serial: 0x12345678,
+            name: "typed",
+        }
+    }
+
+    #[test]
+    fn round_trip_typed() {
+        let im: InfoMem<Typed<Board>> = InfoMem {
+            user: Some(Typed(board())),
+            ..Default::default()
+        };
+
+        let ser = to_allocvec(&im).unwrap();
+        let de: InfoMem<Typed<Board>> = from_bytes(&ser).unwrap();
+
+        assert_eq!(im, de);
+    }
+
+    #[test]
+    fn typed_is_byte_blob() {
+        let typed: InfoMem<Typed<Board>> = InfoMem {
+            user: Some(Typed(board())),
+            ..Default::default()
+        };
+
+        let blob = to_allocvec(&board()).unwrap();
+        let opaque: InfoMem<Vec<u8>> = InfoMem {
+            user: Some(blob.clone()),
+            ..Default::default()
+        };
+
+        let ser = to_allocvec(&typed).unwrap();
+        assert_eq!(ser, to_allocvec(&opaque).unwrap());
+
+        let de: InfoMem = from_bytes(&ser).unwrap();
+        assert_eq!(de.user, Some(&blob[..]));
+        assert_eq!(de.user_typed(), Ok(Some(board())));
+    }
+
+    #[test]
+    fn long_payload_varint() {
+        let payload = [0xa5u8; 200];
+        let im: InfoMem<Typed<&[u8]>> = InfoMem {
+            user: Some(Typed(&payload)),
+            ..Default::default()
+        };
+
+        let mut buf = [0; 256];
+        let ser = to_slice_magic(&im, &mut buf).unwrap();
+        let de: InfoMem<Typed<&[u8]>> = from_bytes_magic(ser).unwrap();
+
+        assert_eq!(im, de);
+    }
+}